@@ -0,0 +1,113 @@
+use core::ffi::{c_char, CStr};
+
+use crate::tag::TagSize;
+
+/// What the app was asked to do by the arguments it was launched with.
+pub enum Launch<'a> {
+    /// Open an image in the GUI, e.g. from the Archive.
+    Open(OpenArgs<'a>),
+    /// Write an image without the GUI, e.g. from the CLI.
+    Write(WriteArgs<'a>),
+}
+
+pub struct OpenArgs<'a> {
+    pub file_path: &'a str,
+    pub tag_size: Option<TagSize>,
+}
+
+pub struct WriteArgs<'a> {
+    pub file_path: &'a str,
+    pub tag_size: Option<TagSize>,
+    pub count: u32,
+    pub force: bool,
+    /// Seconds to wait for each tag to be presented or taken away.
+    pub timeout: u32,
+}
+
+pub enum ArgsError {
+    MissingPath,
+    BadSize,
+    BadCount,
+    BadTimeout,
+    UnknownOption,
+}
+
+impl ArgsError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::MissingPath => "missing image path",
+            Self::BadSize => "--size must be 2.9, 4.2 or 7.5",
+            Self::BadCount => "--count must be a number",
+            Self::BadTimeout => "--timeout must be a number of seconds",
+            Self::UnknownOption => "unknown option",
+        }
+    }
+}
+
+pub const USAGE: &str =
+    "usage: write <image path> [--size 2.9|4.2|7.5] [--count n] [--force] [--timeout s]";
+
+/// Parses the launch arguments, returning `None` when the app was started without any.
+pub unsafe fn parse<'a>(args: *const c_char) -> Option<Result<Launch<'a>, ArgsError>> {
+    if args.is_null() {
+        return None;
+    }
+    let args = CStr::from_ptr(args).to_str().ok()?.trim();
+    if args.is_empty() {
+        return None;
+    }
+    match args.strip_prefix("write ") {
+        Some(args) => Some(parse_write(args).map(Launch::Write)),
+        None => Some(Ok(Launch::Open(parse_open(args)))),
+    }
+}
+
+/// Parses `<file path> [panel]`. The path may contain spaces, so the panel is only taken from
+/// the last word if it names a known panel.
+fn parse_open(args: &str) -> OpenArgs {
+    if let Some((file_path, name)) = args.rsplit_once(' ') {
+        if let Some(tag_size) = TagSize::from_name(name) {
+            return OpenArgs {
+                file_path: file_path.trim_end(),
+                tag_size: Some(tag_size),
+            };
+        }
+    }
+    OpenArgs {
+        file_path: args,
+        tag_size: None,
+    }
+}
+
+/// Parses `<file path> [--size <panel>] [--count <n>] [--force] [--timeout <s>]`. Words are split
+/// on whitespace, so the path can't contain spaces.
+fn parse_write(args: &str) -> Result<WriteArgs, ArgsError> {
+    let mut words = args.split_whitespace();
+    let file_path = words.next().ok_or(ArgsError::MissingPath)?;
+    let mut write_args = WriteArgs {
+        file_path,
+        tag_size: None,
+        count: 1,
+        force: false,
+        timeout: 60,
+    };
+    while let Some(option) = words.next() {
+        match option {
+            "--size" => {
+                let name = words.next().ok_or(ArgsError::BadSize)?;
+                write_args.tag_size = Some(TagSize::from_name(name).ok_or(ArgsError::BadSize)?);
+            }
+            "--count" => {
+                let count = words.next().ok_or(ArgsError::BadCount)?;
+                write_args.count = count.parse().map_err(|_| ArgsError::BadCount)?;
+            }
+            "--force" => write_args.force = true,
+            "--timeout" => {
+                let timeout = words.next().ok_or(ArgsError::BadTimeout)?;
+                write_args.timeout = timeout.parse().map_err(|_| ArgsError::BadTimeout)?;
+            }
+            _ => return Err(ArgsError::UnknownOption),
+        }
+    }
+    Ok(write_args)
+}
@@ -0,0 +1,450 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+/// Modules of white space scanners need on either side of the bars.
+pub const QUIET_ZONE: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Symbology {
+    Code128 = 0,
+    Ean13 = 1,
+    UpcA = 2,
+    Code39 = 3,
+    /// Drawn by `datamatrix` and `pdf417`, the others are linear.
+    DataMatrix = 4,
+    Pdf417 = 5,
+}
+
+impl Symbology {
+    pub const ALL: [Symbology; 6] = [
+        Self::Code128,
+        Self::Ean13,
+        Self::UpcA,
+        Self::Code39,
+        Self::DataMatrix,
+        Self::Pdf417,
+    ];
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Parses a name as written in templates, e.g. `ean13`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "code128" => Some(Self::Code128),
+            "ean13" => Some(Self::Ean13),
+            "upca" => Some(Self::UpcA),
+            "code39" => Some(Self::Code39),
+            "datamatrix" => Some(Self::DataMatrix),
+            "pdf417" => Some(Self::Pdf417),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> &'static CStr {
+        match self {
+            Self::Code128 => c"Code 128",
+            Self::Ean13 => c"EAN-13",
+            Self::UpcA => c"UPC-A",
+            Self::Code39 => c"Code 39",
+            Self::DataMatrix => c"DataMatrix",
+            Self::Pdf417 => c"PDF417",
+        }
+    }
+}
+
+/// Bar and space widths of each Code 128 value, the last one is the stop pattern.
+const CODE128: [&[u8]; 107] = [
+    b"212222", b"222122", b"222221", b"121223", b"121322", b"131222", b"122213", b"122312",
+    b"132212", b"221213", b"221312", b"231212", b"112232", b"122132", b"122231", b"113222",
+    b"123122", b"123221", b"223211", b"221132", b"221231", b"213212", b"223112", b"312131",
+    b"311222", b"321122", b"321221", b"312212", b"322112", b"322211", b"212123", b"212321",
+    b"232121", b"111323", b"131123", b"131321", b"112313", b"132113", b"132311", b"211313",
+    b"231113", b"231311", b"112133", b"112331", b"132131", b"113123", b"113321", b"133121",
+    b"313121", b"211331", b"231131", b"213113", b"213311", b"213131", b"311123", b"311321",
+    b"331121", b"312113", b"312311", b"332111", b"314111", b"221411", b"431111", b"111224",
+    b"111422", b"121124", b"121421", b"141122", b"141221", b"112214", b"112412", b"122114",
+    b"122411", b"142112", b"142211", b"241211", b"221114", b"413111", b"241112", b"134111",
+    b"111242", b"121142", b"121241", b"114212", b"124112", b"124211", b"411212", b"421112",
+    b"421211", b"212141", b"214121", b"412121", b"111143", b"111341", b"131141", b"114113",
+    b"114311", b"411113", b"411311", b"113141", b"114131", b"311141", b"411131", b"211412",
+    b"211214", b"211232", b"2331112",
+];
+/// Switches to code set C in code set B, and back in code set C.
+const CODE_C: usize = 99;
+const CODE_B: usize = 100;
+const START_B: usize = 104;
+const START_C: usize = 105;
+const STOP: usize = 106;
+
+/// Code 39 characters, in groups of ten sharing which space is wide.
+const CODE39: &[u8; 40] = b"1234567890ABCDEFGHIJKLMNOPQRSTUVWXYZ-. *";
+/// Wide bars of each position in a group, as in 2 of 5 codes.
+const CODE39_BARS: [u8; 10] = [
+    0b10001, 0b01001, 0b11000, 0b00101, 0b10100, 0b01100, 0b00011, 0b10010, 0b01010, 0b00110,
+];
+const CODE39_SPACES: [u8; 4] = [0b0100, 0b0010, 0b0001, 0b1000];
+/// The characters with three wide spaces and no wide bars.
+const CODE39_EXTRA: [(u8, u8); 4] = [
+    (b'$', 0b1110),
+    (b'/', 0b1101),
+    (b'+', 0b1011),
+    (b'%', 0b0111),
+];
+const WIDE: usize = 3;
+
+/// Left half digit patterns with odd parity, the right half and even parity ones derive from them.
+const EAN_L: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+/// Which left half digits use even parity, encoding the first digit.
+const EAN_PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+/// Bars of a linear barcode, one entry per module, with the text to print under them.
+pub struct Barcode {
+    modules: Vec<bool>,
+    text: String,
+}
+
+impl Barcode {
+    /// Checks the data and encodes it. Check digits are added when left out, and verified when given.
+    pub fn encode(symbology: Symbology, data: &str) -> Result<Self, &'static CStr> {
+        if data.is_empty() {
+            return Err(c"No data");
+        }
+        match symbology {
+            Symbology::Code128 => code128(data),
+            Symbology::Ean13 => ean13(data, 12),
+            Symbology::UpcA => ean13(data, 11),
+            Symbology::Code39 => code39(data),
+            Symbology::DataMatrix | Symbology::Pdf417 => Err(c"Not a linear code"),
+        }
+    }
+
+    pub fn modules(&self) -> &[bool] {
+        &self.modules
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Appends alternating bars and spaces of the given widths, starting with a bar.
+    fn push_widths(&mut self, widths: impl IntoIterator<Item = usize>) {
+        for (i, width) in widths.into_iter().enumerate() {
+            self.modules.resize(self.modules.len() + width, i % 2 == 0);
+        }
+    }
+
+    fn push_bits(&mut self, bits: u8, count: usize) {
+        for i in (0..count).rev() {
+            self.modules.push(bits >> i & 1 != 0);
+        }
+    }
+}
+
+/// Digits at the start of `bytes`.
+fn digit_run(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count()
+}
+
+/// Uses code set C, two digits a value, for runs of four or more digits and for data of an even
+/// number of digits, and code set B for anything else printable.
+fn code128(data: &str) -> Result<Barcode, &'static CStr> {
+    let bytes = data.as_bytes();
+    if !bytes.iter().all(|byte| (b' '..=b'~').contains(byte)) {
+        return Err(c"Unsupported character");
+    }
+    let leading = digit_run(bytes);
+    let mut set_c = leading >= 4 || (leading == bytes.len() && leading % 2 == 0);
+    let mut values = vec![if set_c { START_C } else { START_B }];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let run = digit_run(rest);
+        if set_c && run < 2 {
+            values.push(CODE_B);
+            set_c = false;
+        } else if !set_c && run >= 4 && run % 2 == 0 {
+            // an odd run starts with a digit in code set B
+            values.push(CODE_C);
+            set_c = true;
+        }
+        if set_c {
+            values.push(((rest[0] - b'0') * 10 + rest[1] - b'0') as usize);
+            rest = &rest[2..];
+        } else {
+            values.push((rest[0] - b' ') as usize);
+            rest = &rest[1..];
+        }
+    }
+    let check = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| i.max(1) * value)
+        .sum::<usize>()
+        % 103;
+
+    let mut barcode = Barcode {
+        modules: Vec::new(),
+        text: data.into(),
+    };
+    for value in values.into_iter().chain([check, STOP]) {
+        barcode.push_widths(CODE128[value].iter().map(|width| (width - b'0') as usize));
+    }
+    Ok(barcode)
+}
+
+/// Encodes `digits` data digits plus a check digit. UPC-A is EAN-13 with a leading zero.
+fn ean13(data: &str, digits: usize) -> Result<Barcode, &'static CStr> {
+    if !data.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(c"Digits only");
+    }
+    if data.len() != digits && data.len() != digits + 1 {
+        return Err(if digits == 12 {
+            c"Needs 12 or 13 digits"
+        } else {
+            c"Needs 11 or 12 digits"
+        });
+    }
+    let mut values: Vec<u8> = data.bytes().map(|byte| byte - b'0').collect();
+    if digits == 11 {
+        values.insert(0, 0);
+    }
+    // weights 1 and 3 alternate from the left
+    let sum: u32 = values[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, &digit)| digit as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    let check = ((10 - sum % 10) % 10) as u8;
+    match values.get(12) {
+        Some(&given) if given != check => return Err(c"Wrong check digit"),
+        Some(_) => {}
+        None => values.push(check),
+    }
+
+    let mut text = String::new();
+    let shown = if digits == 11 {
+        &values[1..]
+    } else {
+        &values[..]
+    };
+    text.extend(shown.iter().map(|&digit| (b'0' + digit) as char));
+    let mut barcode = Barcode {
+        modules: Vec::new(),
+        text,
+    };
+    barcode.push_bits(0b101, 3);
+    let parity = EAN_PARITY[values[0] as usize];
+    for (i, &digit) in values[1..7].iter().enumerate() {
+        let left = EAN_L[digit as usize];
+        if parity >> (5 - i) & 1 != 0 {
+            // even parity, the right half pattern mirrored
+            barcode.push_bits(!(left.reverse_bits() >> 1) & 0x7F, 7);
+        } else {
+            barcode.push_bits(left, 7);
+        }
+    }
+    barcode.push_bits(0b01010, 5);
+    for &digit in &values[7..] {
+        barcode.push_bits(!EAN_L[digit as usize] & 0x7F, 7);
+    }
+    barcode.push_bits(0b101, 3);
+    Ok(barcode)
+}
+
+/// Encodes with wide elements three modules wide, between `*` start and stop characters.
+fn code39(data: &str) -> Result<Barcode, &'static CStr> {
+    let mut barcode = Barcode {
+        modules: Vec::new(),
+        text: data.into(),
+    };
+    let characters = core::iter::once(b'*')
+        .chain(data.bytes())
+        .chain(core::iter::once(b'*'));
+    for (i, character) in characters.enumerate() {
+        if character == b'*' && i != 0 && i != data.len() + 1 {
+            return Err(c"Unsupported character");
+        }
+        let (bars, spaces) = if let Some(index) = CODE39.iter().position(|&c| c == character) {
+            (CODE39_BARS[index % 10], CODE39_SPACES[index / 10])
+        } else if let Some(&(_, spaces)) = CODE39_EXTRA.iter().find(|(c, _)| *c == character) {
+            (0, spaces)
+        } else {
+            return Err(c"Unsupported character");
+        };
+        if i > 0 {
+            // narrow gap between characters
+            barcode.modules.push(false);
+        }
+        let widths = (0..9).map(|element| {
+            let wide = if element % 2 == 0 {
+                bars >> (4 - element / 2) & 1
+            } else {
+                spaces >> (3 - element / 2) & 1
+            };
+            if wide != 0 {
+                WIDE
+            } else {
+                1
+            }
+        });
+        barcode.push_widths(widths);
+    }
+    Ok(barcode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(barcode: &Barcode) -> String {
+        barcode
+            .modules()
+            .iter()
+            .map(|&bar| if bar { '1' } else { '0' })
+            .collect()
+    }
+
+    /// Alternating bars and spaces of the given widths, starting with a bar.
+    fn from_widths(patterns: &[&str]) -> String {
+        let widths = patterns.iter().flat_map(|pattern| pattern.bytes());
+        let mut bars = String::new();
+        for (i, width) in widths.enumerate() {
+            let module = if i % 2 == 0 { '1' } else { '0' };
+            bars.extend(core::iter::repeat_n(module, (width - b'0') as usize));
+        }
+        bars
+    }
+
+    #[test]
+    fn encodes_code128_digits_in_set_c() {
+        let barcode = Barcode::encode(Symbology::Code128, "12").ok().unwrap();
+        // start C, 12, check (105 + 12) % 103 = 14, stop
+        let expected = ["211232", "112232", "122231", "2331112"];
+        assert_eq!(bars(&barcode), from_widths(&expected));
+        assert_eq!(barcode.text(), "12");
+    }
+
+    #[test]
+    fn switches_code128_sets() {
+        let barcode = Barcode::encode(Symbology::Code128, "AB12345678")
+            .ok()
+            .unwrap();
+        // start B, A, B, code C, 12, 34, 56, 78, check 1602 % 103 = 57, stop
+        let expected = [
+            "211214", "111323", "131123", "113141", "112232", "131123", "331121", "241112",
+            "312113", "2331112",
+        ];
+        assert_eq!(bars(&barcode), from_widths(&expected));
+
+        let barcode = Barcode::encode(Symbology::Code128, "1234A").ok().unwrap();
+        // start C, 12, 34, code B, A, check 617 % 103 = 102, stop
+        let expected = [
+            "211232", "112232", "131123", "114131", "111323", "411131", "2331112",
+        ];
+        assert_eq!(bars(&barcode), from_widths(&expected));
+
+        // a run of three digits costs less in code set B
+        let barcode = Barcode::encode(Symbology::Code128, "A123").ok().unwrap();
+        // start B, A, 1, 2, 3, check 301 % 103 = 95, stop
+        let expected = [
+            "211214", "111323", "123221", "223211", "221132", "114113", "2331112",
+        ];
+        assert_eq!(bars(&barcode), from_widths(&expected));
+    }
+
+    #[test]
+    fn encodes_code39() {
+        let barcode = Barcode::encode(Symbology::Code39, "A-").ok().unwrap();
+        // *, A, - and * with a narrow space between, wide elements three modules
+        let expected = [
+            "131131311",
+            "1",
+            "311113113",
+            "1",
+            "131111313",
+            "1",
+            "131131311",
+        ];
+        assert_eq!(bars(&barcode), from_widths(&expected));
+        assert_eq!(
+            Barcode::encode(Symbology::Code39, "a").err(),
+            Some(c"Unsupported character")
+        );
+    }
+
+    #[test]
+    fn encodes_ean13() {
+        let barcode = Barcode::encode(Symbology::Ean13, "5901234123457")
+            .ok()
+            .unwrap();
+        // guard, 9 L, 0 G, 1 G, 2 L, 3 L, 4 G, centre, 1 2 3 4 5 7 R, guard
+        let expected = [
+            "101", "0001011", "0100111", "0110011", "0010011", "0111101", "0011101", "01010",
+            "1100110", "1101100", "1000010", "1011100", "1001110", "1000100", "101",
+        ];
+        assert_eq!(bars(&barcode), expected.concat());
+        assert_eq!(barcode.text(), "5901234123457");
+    }
+
+    #[test]
+    fn adds_check_digits() {
+        let ean = Barcode::encode(Symbology::Ean13, "590123412345")
+            .ok()
+            .unwrap();
+        assert_eq!(ean.text(), "5901234123457");
+        let upc = Barcode::encode(Symbology::UpcA, "03600029145")
+            .ok()
+            .unwrap();
+        assert_eq!(upc.text(), "036000291452");
+        // UPC-A is EAN-13 with a leading zero
+        let ean = Barcode::encode(Symbology::Ean13, "0036000291452")
+            .ok()
+            .unwrap();
+        assert_eq!(bars(&upc), bars(&ean));
+    }
+
+    #[test]
+    fn rejects_wrong_check_digits() {
+        for (symbology, data) in [
+            (Symbology::Ean13, "5901234123458"),
+            (Symbology::Ean13, "4006381333930"),
+            (Symbology::UpcA, "036000291453"),
+            (Symbology::UpcA, "012345678906"),
+        ] {
+            assert_eq!(
+                Barcode::encode(symbology, data).err(),
+                Some(c"Wrong check digit")
+            );
+        }
+        assert!(Barcode::encode(Symbology::Ean13, "4006381333931").is_ok());
+        assert!(Barcode::encode(Symbology::UpcA, "012345678905").is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_lengths_and_characters() {
+        assert_eq!(
+            Barcode::encode(Symbology::Ean13, "12345").err(),
+            Some(c"Needs 12 or 13 digits")
+        );
+        assert_eq!(
+            Barcode::encode(Symbology::UpcA, "1234567890123").err(),
+            Some(c"Needs 11 or 12 digits")
+        );
+        assert_eq!(
+            Barcode::encode(Symbology::UpcA, "03600029145A").err(),
+            Some(c"Digits only")
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::ffi::CString;
+use core::ffi::{c_char, CStr};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use crate::barcode::{Barcode, Symbology, QUIET_ZONE};
+use crate::datamatrix::{self, DataMatrix};
+use crate::framebuffer::Framebuffer;
+use crate::label::{builtin_faces, draw_text, Align, Area, Canvas};
+use crate::pdf417::{self, Pdf417};
+use crate::settings::Settings;
+
+/// Room for the typed data, including the nul.
+pub const INPUT_SIZE: usize = 48;
+
+/// A barcode typed on the Flipper, printed with its text below it.
+pub struct BarcodeLabel {
+    pub input: [c_char; INPUT_SIZE],
+    data: CString,
+    pub symbology: Symbology,
+}
+
+impl BarcodeLabel {
+    pub fn new() -> Self {
+        Self {
+            input: [0; INPUT_SIZE],
+            data: CString::default(),
+            symbology: Symbology::Code128,
+        }
+    }
+
+    pub fn data(&self) -> &CStr {
+        &self.data
+    }
+
+    /// Puts the data in the input to be edited.
+    pub fn edit(&mut self) {
+        self.input = [0; INPUT_SIZE];
+        for (dst, src) in self.input.iter_mut().zip(self.data.as_bytes()) {
+            *dst = *src as c_char;
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.data = unsafe { CStr::from_ptr(self.input.as_ptr()) }.into();
+    }
+
+    /// Draws the code as large as it fits across the panel, with its text below.
+    pub fn render(&self, settings: &Settings) -> Result<Framebuffer, *const c_char> {
+        let data = self.data.to_str().unwrap_or("");
+        let mut framebuffer = Framebuffer::new(settings.tag_size);
+        let mut canvas = Canvas::new(&mut framebuffer, settings.rotation);
+        let (width, height) = (canvas.width, canvas.height);
+        let margin = width.min(height) / 20;
+        let area = Area {
+            left: margin,
+            top: margin,
+            width: width - 2 * margin,
+            height: height - 2 * margin,
+        };
+        draw_barcode(&mut canvas, self.symbology, data, &area, true)?;
+
+        if settings.invert {
+            framebuffer.invert();
+        }
+        Ok(framebuffer)
+    }
+}
+
+/// Draws the code in the area, with the text it encodes in the bottom fifth when `caption` is set.
+pub fn draw_barcode(
+    canvas: &mut Canvas,
+    symbology: Symbology,
+    data: &str,
+    area: &Area,
+    caption: bool,
+) -> Result<(), *const c_char> {
+    let text_height = if caption { area.height / 5 } else { 0 };
+    let code_area = Area {
+        left: area.left,
+        top: area.top,
+        width: area.width,
+        height: area.height - text_height,
+    };
+
+    let barcode;
+    let text = match symbology {
+        Symbology::DataMatrix => {
+            let matrix = DataMatrix::encode(data.as_bytes(), code_area.width, code_area.height)
+                .ok_or(c_string!("Data too long for panel"))?;
+            let size = (matrix.cols(), matrix.rows());
+            let module = |x, y| matrix.module(x, y);
+            draw_modules(canvas, size, datamatrix::QUIET_ZONE, module, &code_area);
+            data
+        }
+        Symbology::Pdf417 => {
+            let symbol = Pdf417::encode(data.as_bytes(), code_area.width, code_area.height)
+                .ok_or(c_string!("Data too long for panel"))?;
+            let size = (symbol.cols(), symbol.rows());
+            let module = |x, y| symbol.module(x, y);
+            draw_modules(canvas, size, pdf417::QUIET_ZONE, module, &code_area);
+            data
+        }
+        _ => {
+            barcode = Barcode::encode(symbology, data).map_err(CStr::as_ptr)?;
+            draw_bars(canvas, &barcode, &code_area)?;
+            barcode.text()
+        }
+    };
+
+    if caption {
+        let text_area = Area {
+            left: area.left,
+            top: area.top + area.height - text_height,
+            width: area.width,
+            height: text_height,
+        };
+        draw_text(canvas, &builtin_faces(), &[text], Align::Center, &text_area);
+    }
+    Ok(())
+}
+
+/// Draws full height bars with the widest modules that fit, centered across the area.
+fn draw_bars(canvas: &mut Canvas, barcode: &Barcode, area: &Area) -> Result<(), *const c_char> {
+    let modules = barcode.modules();
+    let module = area.width / (modules.len() + 2 * QUIET_ZONE);
+    if module == 0 {
+        return Err(c_string!("Barcode too long for panel"));
+    }
+    let left = area.left + (area.width - modules.len() * module) / 2;
+    for (i, _) in modules.iter().enumerate().filter(|(_, &bar)| bar) {
+        let corner = Point::new((left + i * module) as i32, area.top as i32);
+        let size = Size::new(module as u32, area.height as u32);
+        let _ = canvas.fill_solid(&Rectangle::new(corner, size), BinaryColor::On);
+    }
+    Ok(())
+}
+
+/// Draws a 2D symbol of `cols` by `rows` modules with the largest modules the area allows,
+/// centered in it. The encoders pick a size that fits.
+fn draw_modules(
+    canvas: &mut Canvas,
+    (cols, rows): (usize, usize),
+    quiet_zone: usize,
+    is_dark: impl Fn(usize, usize) -> bool,
+    area: &Area,
+) {
+    let module = (area.width / (cols + 2 * quiet_zone)).min(area.height / (rows + 2 * quiet_zone));
+    let left = area.left + (area.width - cols * module) / 2;
+    let top = area.top + (area.height - rows * module) / 2;
+    for y in 0..rows {
+        for x in 0..cols {
+            if is_dark(x, y) {
+                let corner = Point::new((left + x * module) as i32, (top + y * module) as i32);
+                let size = Size::new_equal(module as u32);
+                let _ = canvas.fill_solid(&Rectangle::new(corner, size), BinaryColor::On);
+            }
+        }
+    }
+}
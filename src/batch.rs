@@ -0,0 +1,168 @@
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use core::mem::MaybeUninit;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::image::{self, Image};
+use crate::merge::Merge;
+use crate::settings::Settings;
+
+/// What a batch writes, one per tag.
+enum Items {
+    /// Image files, sorted by name.
+    Images(Vec<FuriString>),
+    /// Rows of a CSV file filled into a template.
+    Rows(Merge),
+}
+
+/// Images or merged rows queued to be written to successive tags.
+pub struct Batch {
+    items: Items,
+    index: usize,
+    pub written: usize,
+    pub skipped: usize,
+}
+
+impl Batch {
+    /// Queues every image in the folder containing `file_path`, sorted by name.
+    pub fn from_folder(file_path: &CStr) -> Option<Self> {
+        let file_path = file_path.to_str().ok()?;
+        let (folder, _) = file_path.rsplit_once('/')?;
+        let mut folder_path = FuriString::new();
+        let _ = uwrite!(folder_path, "{}", folder);
+
+        let mut paths = Vec::new();
+        unsafe {
+            let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+            let dir = sys::storage_file_alloc(storage);
+            if sys::storage_dir_open(dir, folder_path.as_c_str().as_ptr()) {
+                let mut info = MaybeUninit::<sys::FileInfo>::zeroed().assume_init();
+                let mut name = [0 as c_char; 256];
+                while sys::storage_dir_read(dir, &mut info, name.as_mut_ptr(), name.len() as u16) {
+                    let name = CStr::from_ptr(name.as_ptr());
+                    if sys::file_info_is_dir(&info) || !image::is_image(name) {
+                        continue;
+                    }
+                    let Ok(name) = name.to_str() else { continue };
+                    let mut path = FuriString::new();
+                    let _ = uwrite!(path, "{}/{}", folder, name);
+                    paths.push(path);
+                }
+                sys::storage_dir_close(dir);
+            }
+            sys::storage_file_free(dir);
+            sys::furi_record_close(c_string!("storage"));
+        }
+
+        if paths.is_empty() {
+            return None;
+        }
+        paths.sort_by(|a, b| a.as_c_str().cmp(b.as_c_str()));
+        Some(Batch {
+            items: Items::Images(paths),
+            index: 0,
+            written: 0,
+            skipped: 0,
+        })
+    }
+
+    /// Queues the rows of a merge, starting at the first one not written before.
+    pub fn from_merge(merge: Merge) -> Self {
+        let mut batch = Batch {
+            items: Items::Rows(merge),
+            index: 0,
+            written: 0,
+            skipped: 0,
+        };
+        batch.skip_written();
+        batch
+    }
+
+    pub fn merge(&self) -> Option<&Merge> {
+        match &self.items {
+            Items::Images(_) => None,
+            Items::Rows(merge) => Some(merge),
+        }
+    }
+
+    /// Starts a merge over from the first row, forgetting the rows written before.
+    pub fn restart(&mut self) {
+        if let Items::Rows(merge) = &mut self.items {
+            merge.restart();
+        }
+        self.index = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.items {
+            Items::Images(paths) => paths.len(),
+            Items::Rows(merge) => merge.len(),
+        }
+    }
+
+    /// 1-based position of the current image, for display.
+    pub fn position(&self) -> usize {
+        self.index + 1
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.index >= self.len()
+    }
+
+    /// Loads the current image, setting the panel its size fits, or renders the current row.
+    pub fn load(&mut self, settings: &mut Settings) -> Result<Image, *const c_char> {
+        match &mut self.items {
+            Items::Images(paths) => {
+                let file_path = paths[self.index].as_c_str();
+                image::detect_tag_size(file_path, settings.rotation)
+                    .and_then(|tag_size| {
+                        settings.tag_size = tag_size;
+                        image::load(file_path, settings)
+                    })
+                    .map_err(|error| error.message())
+            }
+            Items::Rows(merge) => {
+                let framebuffer = merge.render(self.index, settings)?;
+                Ok(Image::from_framebuffer(framebuffer, merge.key()))
+            }
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.index += 1;
+        self.skip_written();
+    }
+
+    /// Counts the current item as written, saving a merge's progress, and moves on.
+    pub fn mark_written(&mut self) {
+        self.written += 1;
+        if let Items::Rows(merge) = &mut self.items {
+            merge.mark_written(self.index);
+        }
+        self.advance();
+    }
+
+    fn skip_written(&mut self) {
+        if let Items::Rows(merge) = &self.items {
+            while self.index < merge.len() && merge.is_written(self.index) {
+                self.index += 1;
+            }
+        }
+    }
+
+    /// File name of the current image without its folder, or the key fields of the current row.
+    pub fn name(&self) -> &str {
+        match &self.items {
+            Items::Images(paths) => {
+                let path = paths
+                    .get(self.index)
+                    .and_then(|path| path.as_c_str().to_str().ok());
+                path.and_then(|path| path.rsplit('/').next()).unwrap_or("")
+            }
+            Items::Rows(merge) => merge.key(),
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! BDF bitmap fonts, parsed line by line so only the glyphs a label needs are kept. Has no
+//! Flipper dependencies so the host tools can build it too, `fonts` reads the files.
+
+use alloc::vec::Vec;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::renderer::{TextMetrics, TextRenderer};
+use embedded_graphics::text::Baseline;
+
+/// Drawn for characters the font doesn't have.
+const REPLACEMENT: char = '?';
+
+/// A glyph bitmap with its placement relative to the pen position on the baseline.
+struct Glyph {
+    code: u32,
+    /// How far the pen moves right after the glyph, the DWIDTH of the glyph.
+    advance: i32,
+    width: u32,
+    height: u32,
+    x_offset: i32,
+    y_offset: i32,
+    /// Rows from the top, each padded to whole bytes, most significant bit first.
+    bitmap: Vec<u8>,
+}
+
+impl Glyph {
+    fn pixel(&self, x: u32, y: u32) -> bool {
+        let stride = self.width.div_ceil(8);
+        let byte = self.bitmap[(y * stride + x / 8) as usize];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// A font loaded from a BDF file, with only the glyphs that were asked for.
+pub struct BdfFont {
+    ascent: i32,
+    descent: i32,
+    glyphs: Vec<Glyph>,
+}
+
+impl BdfFont {
+    pub fn line_height(&self) -> u32 {
+        (self.ascent + self.descent).max(1) as u32
+    }
+
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        let find = |code: u32| {
+            self.glyphs
+                .binary_search_by_key(&code, |glyph| glyph.code)
+                .ok()
+                .map(|i| &self.glyphs[i])
+        };
+        find(c as u32).or_else(|| find(REPLACEMENT as u32))
+    }
+
+    /// Width of the text in pixels, the sum of the glyph advances.
+    pub fn width(&self, text: &str) -> u32 {
+        text.chars()
+            .filter_map(|c| self.glyph(c))
+            .map(|glyph| glyph.advance)
+            .sum::<i32>()
+            .max(0) as u32
+    }
+
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        match baseline {
+            Baseline::Top => self.ascent,
+            Baseline::Bottom => -self.descent,
+            Baseline::Middle => self.ascent - (self.ascent + self.descent) / 2,
+            Baseline::Alphabetic => 0,
+        }
+    }
+}
+
+/// Reads a BDF file line by line, keeping the glyphs `wanted` accepts and the replacement.
+pub struct Parser<F> {
+    wanted: F,
+    ascent: Option<i32>,
+    descent: Option<i32>,
+    bounding_box: Option<(i32, i32)>,
+    glyphs: Vec<Glyph>,
+    glyph: Option<Glyph>,
+    in_bitmap: bool,
+}
+
+impl<F: Fn(char) -> bool> Parser<F> {
+    pub fn new(wanted: F) -> Self {
+        Self {
+            wanted,
+            ascent: None,
+            descent: None,
+            bounding_box: None,
+            glyphs: Vec::new(),
+            glyph: None,
+            in_bitmap: false,
+        }
+    }
+
+    pub fn line(&mut self, line: &str) {
+        let mut words = line.split_ascii_whitespace();
+        let Some(keyword) = words.next() else {
+            return;
+        };
+        let mut number = || words.next().and_then(|word| word.parse::<i32>().ok());
+
+        if self.in_bitmap {
+            if keyword == "ENDCHAR" {
+                self.in_bitmap = false;
+                if let Some(glyph) = self.glyph.take() {
+                    if glyph.bitmap.len() == (glyph.width.div_ceil(8) * glyph.height) as usize {
+                        self.glyphs.push(glyph);
+                    }
+                }
+            } else if let Some(glyph) = &mut self.glyph {
+                // rows may carry more padding bytes than the width needs
+                let stride = glyph.width.div_ceil(8) as usize;
+                let row: Option<Vec<u8>> = (0..keyword.len() / 2)
+                    .map(|i| u8::from_str_radix(keyword.get(2 * i..2 * i + 2)?, 16).ok())
+                    .collect();
+                if let Some(row) = row {
+                    glyph
+                        .bitmap
+                        .extend(row.iter().chain([0; 8].iter()).take(stride));
+                }
+            }
+            return;
+        }
+
+        match keyword {
+            "FONT_ASCENT" => self.ascent = number(),
+            "FONT_DESCENT" => self.descent = number(),
+            "FONTBOUNDINGBOX" => {
+                let (_, height, _, y_offset) = (number(), number(), number(), number());
+                if let (Some(height), Some(y_offset)) = (height, y_offset) {
+                    self.bounding_box = Some((height, y_offset));
+                }
+            }
+            "ENCODING" => {
+                self.glyph = number()
+                    .and_then(|code| u32::try_from(code).ok())
+                    .filter(|&code| {
+                        char::from_u32(code).is_some_and(|c| c == REPLACEMENT || (self.wanted)(c))
+                    })
+                    .map(|code| Glyph {
+                        code,
+                        advance: 0,
+                        width: 0,
+                        height: 0,
+                        x_offset: 0,
+                        y_offset: 0,
+                        bitmap: Vec::new(),
+                    });
+            }
+            "DWIDTH" => {
+                if let (Some(glyph), Some(advance)) = (&mut self.glyph, number()) {
+                    glyph.advance = advance;
+                }
+            }
+            "BBX" => {
+                let bbx = (number(), number(), number(), number());
+                if let (Some(glyph), (Some(w), Some(h), Some(x), Some(y))) = (&mut self.glyph, bbx)
+                {
+                    glyph.width = w.max(0) as u32;
+                    glyph.height = h.max(0) as u32;
+                    glyph.x_offset = x;
+                    glyph.y_offset = y;
+                }
+            }
+            "BITMAP" => self.in_bitmap = true,
+            "ENDCHAR" => self.glyph = None,
+            _ => {}
+        }
+    }
+
+    /// The font, `None` if it has no glyphs wanted or no height.
+    pub fn finish(mut self) -> Option<BdfFont> {
+        let (height, y_offset) = self.bounding_box.unwrap_or((0, 0));
+        let ascent = self.ascent.unwrap_or(height + y_offset);
+        let descent = self.descent.unwrap_or(-y_offset);
+        if self.glyphs.is_empty() || ascent + descent <= 0 {
+            return None;
+        }
+        self.glyphs.sort_by_key(|glyph| glyph.code);
+        self.glyphs.dedup_by_key(|glyph| glyph.code);
+        Some(BdfFont {
+            ascent,
+            descent,
+            glyphs: self.glyphs,
+        })
+    }
+}
+
+/// Draws text in a BDF font, placing each glyph by its bounding box and moving the pen by its
+/// DWIDTH.
+#[derive(Clone, Copy)]
+pub struct BdfTextStyle<'a> {
+    font: &'a BdfFont,
+    color: BinaryColor,
+}
+
+impl<'a> BdfTextStyle<'a> {
+    pub fn new(font: &'a BdfFont, color: BinaryColor) -> Self {
+        Self { font, color }
+    }
+}
+
+impl TextRenderer for BdfTextStyle<'_> {
+    type Color = BinaryColor;
+
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let y = position.y + self.font.baseline_offset(baseline);
+        let mut x = position.x;
+        for glyph in text.chars().filter_map(|c| self.font.glyph(c)) {
+            let left = x + glyph.x_offset;
+            let top = y - glyph.y_offset - glyph.height as i32;
+            let pixels = (0..glyph.height).flat_map(|row| {
+                (0..glyph.width)
+                    .filter(move |&col| glyph.pixel(col, row))
+                    .map(move |col| {
+                        Pixel(Point::new(left + col as i32, top + row as i32), self.color)
+                    })
+            });
+            target.draw_iter(pixels)?;
+            x += glyph.advance;
+        }
+        Ok(Point::new(x, position.y))
+    }
+
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        _baseline: Baseline,
+        _target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width = self.font.width(text);
+        let top = position.y + self.font.baseline_offset(baseline) - self.font.ascent;
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                Point::new(position.x, top),
+                Size::new(width, self.font.line_height()),
+            ),
+            next_position: position + Point::new(width as i32, 0),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.line_height()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = "STARTFONT 2.1
+FONT -test-fixed-medium-r-normal--8-80-75-75-c-50-iso10646-1
+SIZE 8 75 75
+FONTBOUNDINGBOX 6 8 -1 -2
+STARTPROPERTIES 2
+FONT_ASCENT 6
+FONT_DESCENT 2
+ENDPROPERTIES
+CHARS 3
+STARTCHAR A
+ENCODING 65
+SWIDTH 500 0
+DWIDTH 5 0
+BBX 4 3 0 0
+BITMAP
+60
+90
+F0
+ENDCHAR
+STARTCHAR j
+ENCODING 106
+SWIDTH 300 0
+DWIDTH 3 0
+BBX 3 4 -1 -2
+BITMAP
+20
+20
+A0
+4000
+ENDCHAR
+STARTCHAR question
+ENCODING 63
+SWIDTH 400 0
+DWIDTH 4 0
+BBX 2 2 1 4
+BITMAP
+C0
+40
+ENDCHAR
+ENDFONT
+";
+
+    fn parse(wanted: impl Fn(char) -> bool) -> Option<BdfFont> {
+        let mut parser = Parser::new(wanted);
+        for line in FONT.lines() {
+            parser.line(line);
+        }
+        parser.finish()
+    }
+
+    /// Collects the pixels drawn.
+    struct Pixels(Vec<Point>);
+
+    impl OriginDimensions for Pixels {
+        fn size(&self) -> Size {
+            Size::new(64, 64)
+        }
+    }
+
+    impl DrawTarget for Pixels {
+        type Color = BinaryColor;
+        type Error = core::convert::Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.0
+                .extend(pixels.into_iter().map(|Pixel(point, _)| point));
+            Ok(())
+        }
+    }
+
+    fn draw(font: &BdfFont, text: &str) -> (Vec<Point>, Point) {
+        let mut pixels = Pixels(Vec::new());
+        let style = BdfTextStyle::new(font, BinaryColor::On);
+        let next = style
+            .draw_string(text, Point::new(10, 10), Baseline::Alphabetic, &mut pixels)
+            .unwrap();
+        (pixels.0, next)
+    }
+
+    #[test]
+    fn reads_glyph_bits() {
+        let font = parse(|_| true).unwrap();
+        assert_eq!(font.line_height(), 8);
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!((glyph.width, glyph.height, glyph.advance), (4, 3, 5));
+        assert_eq!(glyph.bitmap, [0x60, 0x90, 0xF0]);
+        // padding bytes past the width are dropped
+        assert_eq!(font.glyph('j').unwrap().bitmap, [0x20, 0x20, 0xA0, 0x40]);
+
+        // the bottom row of A sits on the baseline
+        let (pixels, next) = draw(&font, "A");
+        let expected = [
+            (11, 7),
+            (12, 7),
+            (10, 8),
+            (13, 8),
+            (10, 9),
+            (11, 9),
+            (12, 9),
+            (13, 9),
+        ];
+        let expected: Vec<Point> = expected.iter().map(|&(x, y)| Point::new(x, y)).collect();
+        assert_eq!(pixels, expected);
+        assert_eq!(next, Point::new(15, 10));
+    }
+
+    #[test]
+    fn places_glyphs_by_negative_offsets() {
+        let font = parse(|_| true).unwrap();
+        // one column left of the pen and two rows below the baseline
+        let (pixels, next) = draw(&font, "j");
+        let expected = [(11, 8), (11, 9), (9, 10), (11, 10), (10, 11)];
+        let expected: Vec<Point> = expected.iter().map(|&(x, y)| Point::new(x, y)).collect();
+        assert_eq!(pixels, expected);
+        assert_eq!(next, Point::new(13, 10));
+    }
+
+    #[test]
+    fn draws_the_replacement_for_missing_glyphs() {
+        let font = parse(|c| c == 'A').unwrap();
+        assert!(font.glyph('A').is_some());
+        // not wanted, so not kept, nor in the font at all
+        for c in ['j', 'x'] {
+            assert_eq!(font.glyph(c).unwrap().code, '?' as u32);
+        }
+        assert_eq!(font.width("Ajx"), 5 + 4 + 4);
+
+        // above the baseline by its offset
+        let (pixels, _) = draw(&font, "x");
+        let expected = [(11, 4), (12, 4), (12, 5)];
+        let expected: Vec<Point> = expected.iter().map(|&(x, y)| Point::new(x, y)).collect();
+        assert_eq!(pixels, expected);
+
+        // nothing wanted is still a font of the replacement
+        assert!(parse(|_| false).is_some());
+    }
+}
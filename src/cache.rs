@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::hex;
+
+const FILE_NAME: &str = "cache.csv";
+
+/// Hash of the last image successfully written to each tag, as `uid,hash` lines, so a tag
+/// already showing an image isn't refreshed again.
+pub struct Cache {
+    entries: Vec<(Vec<u8>, u32)>,
+}
+
+fn parse_line(line: &str) -> Option<(Vec<u8>, u32)> {
+    let (uid, hash) = line.split_once(',')?;
+    let hash: [u8; 4] = hex::decode(hash.trim())?.try_into().ok()?;
+    Some((hex::decode(uid.trim())?, u32::from_be_bytes(hash)))
+}
+
+impl Cache {
+    pub fn load() -> Self {
+        let entries = files::read_to_end(files::app_data_path(FILE_NAME).as_c_str())
+            .map(|data| files::lines(&data).filter_map(parse_line).collect())
+            .unwrap_or_default();
+        Cache { entries }
+    }
+
+    pub fn get(&self, uid: &[u8]) -> Option<u32> {
+        self.entries
+            .iter()
+            .find(|(entry_uid, _)| entry_uid == uid)
+            .map(|&(_, hash)| hash)
+    }
+
+    pub fn set(&mut self, uid: &[u8], hash: u32) {
+        match self.entries.iter_mut().find(|(entry_uid, _)| entry_uid == uid) {
+            Some(entry) => entry.1 = hash,
+            None => self.entries.push((uid.into(), hash)),
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let mut text = FuriString::new();
+        for (uid, hash) in &self.entries {
+            hex::encode(uid, "", &mut text);
+            let _ = uwrite!(text, ",");
+            hex::encode(&hash.to_be_bytes(), "", &mut text);
+            let _ = uwrite!(text, "\n");
+        }
+        if !files::write(FILE_NAME, text.as_c_str().to_bytes()) {
+            println!("couldn't save cache");
+        }
+    }
+}
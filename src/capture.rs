@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use flipperzero::io::Write;
+use flipperzero::println;
+use flipperzero::storage::File;
+use flipperzero_sys as sys;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::hex;
+use crate::history;
+use crate::transport::{TagInfo, Transport};
+
+/// pcap link type for ISO 14443 frames, which Wireshark decodes.
+const LINKTYPE_ISO_14443: u32 = 264;
+
+/// Event types of the ISO 14443 pseudo header. The poller checks and strips the CRC, so frames
+/// are logged as having it dropped.
+const EVENT_PCD_TO_PICC: u8 = 0xFA;
+const EVENT_PICC_TO_PCD: u8 = 0xFB;
+/// Not an ISO 14443 event. Follows a response ending in a partial byte, with the big endian count
+/// of bits received, as the pseudo header only counts bytes. Wireshark shows it as unknown.
+const EVENT_RX_BITS: u8 = 0x80;
+
+/// Records every frame a transport exchanges to a pcap file in the app data folder, named after
+/// the tag and the time, e.g. `capture-5753445A31306D010203-1700000000.pcap`.
+pub struct Capture<'a> {
+    inner: &'a mut dyn Transport,
+    file: Option<File>,
+    start_tick: u32,
+    start_time: u32,
+}
+
+impl<'a> Capture<'a> {
+    /// Wraps `inner`, passing frames straight through if `enabled` is false or the capture file
+    /// can't be created.
+    pub fn new(inner: &'a mut dyn Transport, tag: &TagInfo, enabled: bool) -> Self {
+        let start_tick = unsafe { sys::furi_get_tick() };
+        let start_time = unsafe { sys::furi_hal_rtc_get_timestamp() };
+        let mut capture = Self {
+            inner,
+            file: None,
+            start_tick,
+            start_time,
+        };
+        if !enabled {
+            return capture;
+        }
+
+        let mut name = FuriString::new();
+        let _ = uwrite!(name, "capture-");
+        hex::encode(tag.uid(), "", &mut name);
+        let _ = uwrite!(name, "-{}.pcap", start_time);
+        let Some(mut file) = name.as_c_str().to_str().ok().and_then(files::create) else {
+            println!("couldn't create capture file");
+            return capture;
+        };
+
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&0xA1B2_C3D4u32.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes()); // version 2.4
+        header.extend_from_slice(&4u16.to_le_bytes());
+        header.extend_from_slice(&0i32.to_le_bytes()); // times are UTC
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&0xFFFFu32.to_le_bytes()); // snap length
+        header.extend_from_slice(&LINKTYPE_ISO_14443.to_le_bytes());
+        if file.write(&header).is_ok() {
+            capture.file = Some(file);
+        }
+        capture
+    }
+
+    fn record(&mut self, event: u8, data: &[u8]) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let elapsed_ms = history::elapsed_ms(self.start_tick);
+        let len = (4 + data.len()) as u32;
+        let mut packet = Vec::with_capacity(16 + len as usize);
+        packet.extend_from_slice(&(self.start_time + elapsed_ms / 1000).to_le_bytes());
+        packet.extend_from_slice(&(elapsed_ms % 1000 * 1000).to_le_bytes()); // microseconds
+        packet.extend_from_slice(&len.to_le_bytes());
+        packet.extend_from_slice(&len.to_le_bytes());
+        // pseudo header: version, event, big endian length
+        packet.extend_from_slice(&[0, event]);
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(data);
+        if file.write(&packet).is_err() {
+            println!("capture write failure");
+            self.file = None;
+        }
+    }
+}
+
+impl Transport for Capture<'_> {
+    fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], timeout: u32) -> Option<usize> {
+        self.record(EVENT_PCD_TO_PICC, tx);
+        let bits = self.inner.tx_rx(tx, rx, timeout);
+        if let Some(bits) = bits {
+            self.record(EVENT_PICC_TO_PCD, &rx[..bits.div_ceil(8).min(rx.len())]);
+            if bits % 8 != 0 {
+                self.record(EVENT_RX_BITS, &(bits as u16).to_be_bytes());
+            }
+        }
+        bits
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.inner.delay_ms(ms)
+    }
+}
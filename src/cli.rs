@@ -0,0 +1,105 @@
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::args::WriteArgs;
+use crate::cache::Cache;
+use crate::history;
+use crate::image;
+use crate::settings::Settings;
+use crate::writer::{self, ConsoleProgress};
+
+/// Polls `done` every `interval_ms` until it returns something, giving up after `timeout_s`
+/// seconds or when Ctrl-C is pressed in the CLI.
+fn wait<T>(
+    cli: *mut sys::Cli,
+    timeout_s: u32,
+    interval_ms: u32,
+    mut done: impl FnMut() -> Option<T>,
+) -> Result<T, &'static str> {
+    let start = unsafe { sys::furi_get_tick() };
+    loop {
+        if let Some(value) = done() {
+            return Ok(value);
+        }
+        if unsafe { sys::cli_cmd_interrupt_received(cli) } {
+            return Err("interrupted");
+        }
+        if history::elapsed_ms(start) >= timeout_s.saturating_mul(1000) {
+            return Err("timed out");
+        }
+        unsafe { sys::furi_delay_ms(interval_ms) };
+    }
+}
+
+/// Writes an image to `count` tags in turn, printing progress to the console instead of
+/// showing the GUI. Stops early if no tag shows up or is taken away within the timeout, or on
+/// Ctrl-C. Returns the number of tags not written.
+pub fn run(args: WriteArgs) -> i32 {
+    let mut settings = Settings::load();
+    let mut file_path = FuriString::new();
+    let _ = uwrite!(file_path, "{}", args.file_path);
+
+    settings.tag_size = match args.tag_size {
+        Some(tag_size) => tag_size,
+        None => match image::detect_tag_size(file_path.as_c_str(), settings.rotation) {
+            Ok(tag_size) => tag_size,
+            Err(_) => {
+                println!("can't detect panel size of {}", args.file_path);
+                return -1;
+            }
+        },
+    };
+
+    let image = match image::load(file_path.as_c_str(), &settings) {
+        Ok(image) => image,
+        Err(_) => {
+            println!("can't load {}", args.file_path);
+            return -1;
+        }
+    };
+
+    let cli = unsafe { sys::furi_record_open(c_string!("cli")) as *mut sys::Cli };
+    let mut cache = Cache::load();
+    let mut written = 0;
+    for i in 1..=args.count {
+        println!("tag {}/{}: waiting for tag", i, args.count);
+        let tag = match wait(cli, args.timeout, 50, || writer::detect(&settings)) {
+            Ok(tag) => tag,
+            Err(reason) => {
+                println!("tag {}/{}: {}, stopping", i, args.count, reason);
+                break;
+            }
+        };
+
+        let result = if !args.force && writer::is_up_to_date(&image, &tag, &cache) {
+            println!("tag {}/{}: already up to date, use --force to rewrite", i, args.count);
+            Ok(())
+        } else {
+            writer::write_and_log(&image, &settings, &tag, &mut cache, &mut ConsoleProgress)
+        };
+        match result {
+            Ok(()) => {
+                println!("tag {}/{}: ok", i, args.count);
+                written += 1;
+            }
+            Err(error) => println!("tag {}/{}: failed, {}", i, args.count, error.name()),
+        }
+
+        // don't write the same tag twice, wait for it to be taken away
+        let removed = || writer::detect(&settings).is_none().then_some(());
+        if let Err(reason) = wait(cli, args.timeout, 200, removed) {
+            println!(
+                "tag {}/{}: not taken away, {}, stopping",
+                i, args.count, reason
+            );
+            break;
+        }
+    }
+    unsafe { sys::furi_record_close(c_string!("cli")) };
+
+    println!("wrote {} of {} tags", written, args.count);
+    (args.count - written) as i32
+}
@@ -0,0 +1,91 @@
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use flipperzero::furi::string::FuriString;
+use ufmt::uwrite;
+
+use crate::hex;
+use crate::settings::Settings;
+use crate::poller;
+
+/// Room for the typed command, including the nul.
+pub const INPUT_SIZE: usize = 64;
+
+/// Commands kept in the log, oldest are dropped first.
+const MAX_ENTRIES: usize = 16;
+
+/// Raw frames typed by hand and what the tag answered, for exploring the protocol.
+pub struct Console {
+    pub input: [c_char; INPUT_SIZE],
+    entries: Vec<FuriString>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            input: [0; INPUT_SIZE],
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sends the typed command to a tag in the field and logs the responses. `_` separates
+    /// frames sent while the tag stays selected, e.g. `cd0d_cd000a`. With the Console CRC
+    /// setting off the frames go out as typed and responses are shown with their CRC, even a
+    /// wrong one. Returns false without logging anything if no tag showed up, so it can be
+    /// polled until one does.
+    pub fn send(&mut self, settings: &Settings) -> bool {
+        let command = unsafe { CStr::from_ptr(self.input.as_ptr()) }
+            .to_str()
+            .unwrap_or("");
+        let mut entry = FuriString::new();
+        let frames: Option<Vec<Vec<u8>>> = command
+            .split('_')
+            .map(|frame| hex::decode(frame).filter(|bytes| !bytes.is_empty()))
+            .collect();
+        match frames {
+            Some(frames) => {
+                let with_tag = if settings.console_crc {
+                    poller::with_tag
+                } else {
+                    poller::with_tag_raw
+                };
+                let found = with_tag(settings, &mut |_, transport| {
+                    let mut rx = [0; 64];
+                    for frame in &frames {
+                        let _ = uwrite!(entry, "> ");
+                        hex::encode(frame, " ", &mut entry);
+                        match transport.tx_rx(frame, &mut rx, settings.tx_timeout) {
+                            Some(bits) => {
+                                let _ = uwrite!(entry, "\n< {} bits ", bits);
+                                hex::encode(&rx[..bits.div_ceil(8).min(rx.len())], " ", &mut entry);
+                                let _ = uwrite!(entry, "\n");
+                            }
+                            None => {
+                                let _ = uwrite!(entry, "\n< no response\n");
+                            }
+                        }
+                    }
+                });
+                if !found {
+                    return false;
+                }
+            }
+            None => {
+                let _ = uwrite!(entry, "> {}\nbad hex\n", command);
+            }
+        }
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// Writes the log, oldest command first.
+    pub fn log(&self, text: &mut FuriString) {
+        text.clear();
+        for entry in &self.entries {
+            let _ = uwrite!(text, "{}", entry.as_c_str().to_str().unwrap_or(""));
+        }
+    }
+}
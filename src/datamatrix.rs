@@ -0,0 +1,468 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Modules of white space needed around a symbol.
+pub const QUIET_ZONE: usize = 1;
+
+/// An ECC 200 symbol size with its data regions and codeword counts.
+struct SymbolSize {
+    rows: usize,
+    cols: usize,
+    region_rows: usize,
+    region_cols: usize,
+    data: usize,
+    ecc: usize,
+    blocks: usize,
+}
+
+const fn size(
+    rows: usize,
+    cols: usize,
+    region_rows: usize,
+    region_cols: usize,
+    data: usize,
+    ecc: usize,
+    blocks: usize,
+) -> SymbolSize {
+    SymbolSize {
+        rows,
+        cols,
+        region_rows,
+        region_cols,
+        data,
+        ecc,
+        blocks,
+    }
+}
+
+const SIZES: [SymbolSize; 30] = [
+    size(10, 10, 8, 8, 3, 5, 1),
+    size(12, 12, 10, 10, 5, 7, 1),
+    size(14, 14, 12, 12, 8, 10, 1),
+    size(16, 16, 14, 14, 12, 12, 1),
+    size(18, 18, 16, 16, 18, 14, 1),
+    size(20, 20, 18, 18, 22, 18, 1),
+    size(22, 22, 20, 20, 30, 20, 1),
+    size(24, 24, 22, 22, 36, 24, 1),
+    size(26, 26, 24, 24, 44, 28, 1),
+    size(32, 32, 14, 14, 62, 36, 1),
+    size(36, 36, 16, 16, 86, 42, 1),
+    size(40, 40, 18, 18, 114, 48, 1),
+    size(44, 44, 20, 20, 144, 56, 1),
+    size(48, 48, 22, 22, 174, 68, 1),
+    size(52, 52, 24, 24, 204, 84, 2),
+    size(64, 64, 14, 14, 280, 112, 2),
+    size(72, 72, 16, 16, 368, 144, 4),
+    size(80, 80, 18, 18, 456, 192, 4),
+    size(88, 88, 20, 20, 576, 224, 4),
+    size(96, 96, 22, 22, 696, 272, 4),
+    size(104, 104, 24, 24, 816, 336, 6),
+    size(120, 120, 18, 18, 1050, 408, 6),
+    size(132, 132, 20, 20, 1304, 496, 8),
+    size(144, 144, 22, 22, 1558, 620, 10),
+    // rectangular, for wide panels
+    size(8, 18, 6, 16, 5, 7, 1),
+    size(8, 32, 6, 14, 10, 11, 1),
+    size(12, 26, 10, 24, 16, 14, 1),
+    size(12, 36, 10, 16, 22, 18, 1),
+    size(16, 36, 14, 16, 32, 24, 1),
+    size(16, 48, 14, 22, 49, 28, 1),
+];
+
+/// Codeword ending the data, before any padding.
+const PAD: u8 = 129;
+
+/// A DataMatrix ECC 200 symbol.
+pub struct DataMatrix {
+    rows: usize,
+    cols: usize,
+    modules: Vec<bool>,
+}
+
+impl DataMatrix {
+    /// Encodes the data in the symbol size drawn with the largest modules in a `width` by `height`
+    /// pixel area, preferring fewer modules when it's a tie. Returns `None` if it doesn't fit.
+    pub fn encode(data: &[u8], width: usize, height: usize) -> Option<Self> {
+        let codewords = ascii_codewords(data);
+        let module = |size: &SymbolSize| {
+            let across = width / (size.cols + 2 * QUIET_ZONE);
+            let down = height / (size.rows + 2 * QUIET_ZONE);
+            across.min(down)
+        };
+        let size = SIZES
+            .iter()
+            .filter(|size| size.data >= codewords.len() && module(size) > 0)
+            .max_by_key(|size| (module(size), usize::MAX - size.rows * size.cols))?;
+        Some(Self::with_size(codewords, size))
+    }
+
+    fn with_size(mut codewords: Vec<u8>, size: &SymbolSize) -> Self {
+        if codewords.len() < size.data {
+            codewords.push(PAD);
+        }
+        while codewords.len() < size.data {
+            // pseudo random padding so long runs don't form patterns
+            let position = codewords.len() + 1;
+            let pad = PAD as usize + (149 * position) % 253 + 1;
+            codewords.push(if pad > 254 { pad - 254 } else { pad } as u8);
+        }
+        add_ecc(&mut codewords, size);
+
+        let data_rows = size.rows / (size.region_rows + 2) * size.region_rows;
+        let data_cols = size.cols / (size.region_cols + 2) * size.region_cols;
+        let placement = place(data_rows, data_cols);
+
+        let mut symbol = Self {
+            rows: size.rows,
+            cols: size.cols,
+            modules: vec![false; size.rows * size.cols],
+        };
+        for row in 0..size.rows {
+            for col in 0..size.cols {
+                let region_row = row % (size.region_rows + 2);
+                let region_col = col % (size.region_cols + 2);
+                let dark = if region_row == size.region_rows + 1 || region_col == 0 {
+                    // solid L shaped finder on the left and bottom
+                    true
+                } else if region_row == 0 {
+                    // clock tracks along the top and right
+                    col % 2 == 0
+                } else if region_col == size.region_cols + 1 {
+                    row % 2 == 1
+                } else {
+                    let data_row = row / (size.region_rows + 2) * size.region_rows + region_row - 1;
+                    let data_col = col / (size.region_cols + 2) * size.region_cols + region_col - 1;
+                    match placement[data_row * data_cols + data_col] {
+                        Slot::Bit(index, bit) => codewords[index] >> (7 - bit) & 1 != 0,
+                        Slot::Fixed(dark) => dark,
+                    }
+                };
+                symbol.modules[row * size.cols + col] = dark;
+            }
+        }
+        symbol
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.cols + x]
+    }
+}
+
+/// ASCII encodation: digit pairs in one codeword, bytes above 127 after an upper shift.
+fn ascii_codewords(data: &[u8]) -> Vec<u8> {
+    let mut codewords = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        match data.get(i + 1) {
+            Some(next) if byte.is_ascii_digit() && next.is_ascii_digit() => {
+                codewords.push(130 + (byte - b'0') * 10 + (next - b'0'));
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        if byte < 128 {
+            codewords.push(byte + 1);
+        } else {
+            codewords.push(235);
+            codewords.push(byte - 127);
+        }
+        i += 1;
+    }
+    codewords
+}
+
+/// Appends the Reed-Solomon codewords, with the data split into interleaved blocks.
+fn add_ecc(codewords: &mut Vec<u8>, size: &SymbolSize) {
+    let ecc_len = size.ecc / size.blocks;
+    let generator = generator(ecc_len);
+    let mut ecc = vec![0; size.ecc];
+    for block in 0..size.blocks {
+        let mut remainder = vec![0u8; ecc_len];
+        for &codeword in codewords.iter().skip(block).step_by(size.blocks) {
+            let factor = codeword ^ remainder[0];
+            remainder.rotate_left(1);
+            remainder[ecc_len - 1] = 0;
+            for (r, &g) in remainder.iter_mut().zip(&generator[1..]) {
+                *r ^= gf_multiply(g, factor);
+            }
+        }
+        for (i, codeword) in remainder.into_iter().enumerate() {
+            ecc[block + i * size.blocks] = codeword;
+        }
+    }
+    codewords.extend(ecc);
+}
+
+/// Coefficients of the product of (x - 2^i) for i in 1..=degree, highest power first.
+fn generator(degree: usize) -> Vec<u8> {
+    let mut result = vec![1u8];
+    let mut root = 1;
+    for _ in 0..degree {
+        root = gf_multiply(root, 2);
+        let mut next = vec![0u8; result.len() + 1];
+        for (i, &coefficient) in result.iter().enumerate() {
+            next[i] ^= coefficient;
+            next[i + 1] ^= gf_multiply(coefficient, root);
+        }
+        result = next;
+    }
+    result
+}
+
+/// Multiplies in GF(2^8) modulo x^8 + x^5 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x12D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+#[derive(Clone, Copy)]
+enum Slot {
+    /// Bit of a codeword, 0 being the most significant.
+    Bit(usize, u8),
+    /// The fixed corner pattern some sizes have left over.
+    Fixed(bool),
+}
+
+/// Lays the codewords out over the data area in the diagonal ECC 200 pattern.
+fn place(rows: usize, cols: usize) -> Vec<Slot> {
+    let mut placement = Placement {
+        rows: rows as i32,
+        cols: cols as i32,
+        slots: vec![None; rows * cols],
+    };
+    let (nrow, ncol) = (rows as i32, cols as i32);
+    let mut index = 0;
+    let (mut row, mut col) = (4i32, 0i32);
+    loop {
+        if row == nrow && col == 0 {
+            placement.corner(index, 1);
+            index += 1;
+        }
+        if row == nrow - 2 && col == 0 && ncol % 4 != 0 {
+            placement.corner(index, 2);
+            index += 1;
+        }
+        if row == nrow - 2 && col == 0 && ncol % 8 == 4 {
+            placement.corner(index, 3);
+            index += 1;
+        }
+        if row == nrow + 4 && col == 2 && ncol % 8 == 0 {
+            placement.corner(index, 4);
+            index += 1;
+        }
+        // up and to the right
+        loop {
+            if row < nrow && col >= 0 && placement.is_free(row, col) {
+                placement.utah(row, col, index);
+                index += 1;
+            }
+            row -= 2;
+            col += 2;
+            if row < 0 || col >= ncol {
+                break;
+            }
+        }
+        row += 1;
+        col += 3;
+        // down and to the left
+        loop {
+            if row >= 0 && col < ncol && placement.is_free(row, col) {
+                placement.utah(row, col, index);
+                index += 1;
+            }
+            row += 2;
+            col -= 2;
+            if row >= nrow || col < 0 {
+                break;
+            }
+        }
+        row += 3;
+        col += 1;
+        if row >= nrow && col >= ncol {
+            break;
+        }
+    }
+    let slots = &mut placement.slots;
+    if slots[rows * cols - 1].is_none() {
+        slots[rows * cols - 1] = Some(Slot::Fixed(true));
+        slots[(rows - 1) * cols - 2] = Some(Slot::Fixed(true));
+    }
+    slots
+        .iter()
+        .map(|slot| slot.unwrap_or(Slot::Fixed(false)))
+        .collect()
+}
+
+struct Placement {
+    rows: i32,
+    cols: i32,
+    slots: Vec<Option<Slot>>,
+}
+
+impl Placement {
+    fn is_free(&self, row: i32, col: i32) -> bool {
+        self.slots[(row * self.cols + col) as usize].is_none()
+    }
+
+    /// Places a bit, wrapping positions outside the area around to the opposite edge.
+    fn module(&mut self, mut row: i32, mut col: i32, index: usize, bit: u8) {
+        if row < 0 {
+            row += self.rows;
+            col += 4 - (self.rows + 4) % 8;
+        }
+        if col < 0 {
+            col += self.cols;
+            row += 4 - (self.cols + 4) % 8;
+        }
+        self.slots[(row * self.cols + col) as usize] = Some(Slot::Bit(index, bit));
+    }
+
+    /// The usual L shaped codeword with its last bit at `row`, `col`.
+    fn utah(&mut self, row: i32, col: i32, index: usize) {
+        let bits = [
+            (row - 2, col - 2),
+            (row - 2, col - 1),
+            (row - 1, col - 2),
+            (row - 1, col - 1),
+            (row - 1, col),
+            (row, col - 2),
+            (row, col - 1),
+            (row, col),
+        ];
+        for (bit, (row, col)) in bits.into_iter().enumerate() {
+            self.module(row, col, index, bit as u8);
+        }
+    }
+
+    /// Codewords split over the corners in some sizes.
+    fn corner(&mut self, index: usize, kind: u8) {
+        let (r, c) = (self.rows, self.cols);
+        let bits = match kind {
+            1 => [
+                (r - 1, 0),
+                (r - 1, 1),
+                (r - 1, 2),
+                (0, c - 2),
+                (0, c - 1),
+                (1, c - 1),
+                (2, c - 1),
+                (3, c - 1),
+            ],
+            2 => [
+                (r - 3, 0),
+                (r - 2, 0),
+                (r - 1, 0),
+                (0, c - 4),
+                (0, c - 3),
+                (0, c - 2),
+                (0, c - 1),
+                (1, c - 1),
+            ],
+            3 => [
+                (r - 3, 0),
+                (r - 2, 0),
+                (r - 1, 0),
+                (0, c - 2),
+                (0, c - 1),
+                (1, c - 1),
+                (2, c - 1),
+                (3, c - 1),
+            ],
+            _ => [
+                (r - 1, 0),
+                (r - 1, c - 1),
+                (0, c - 3),
+                (0, c - 2),
+                (0, c - 1),
+                (1, c - 3),
+                (1, c - 2),
+                (1, c - 1),
+            ],
+        };
+        for (bit, (row, col)) in bits.into_iter().enumerate() {
+            self.module(row, col, index, bit as u8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_iso_example_codewords() {
+        // the "123456" example of ISO/IEC 16022, a 10x10 symbol
+        let mut codewords = ascii_codewords(b"123456");
+        assert_eq!(codewords, [142, 164, 186]);
+        add_ecc(&mut codewords, &SIZES[0]);
+        assert_eq!(codewords, [142, 164, 186, 114, 25, 5, 88, 102]);
+    }
+
+    #[test]
+    fn places_iso_example_modules() {
+        let symbol = DataMatrix::encode(b"123456", 12, 12).unwrap();
+        let expected = [
+            "#.#.#.#.#.",
+            "##..#.##.#",
+            "##.....#..",
+            "##...###.#",
+            "##....#...",
+            "#.....####",
+            "###.##....",
+            "####.##..#",
+            "#..###.#..",
+            "##########",
+        ];
+        assert_eq!((symbol.rows(), symbol.cols()), (10, 10));
+        for (y, row) in expected.iter().enumerate() {
+            let drawn: String = (0..symbol.cols())
+                .map(|x| if symbol.module(x, y) { '#' } else { '.' })
+                .collect();
+            assert_eq!(drawn, *row, "row {y}");
+        }
+    }
+
+    #[test]
+    fn encodes_ascii_digits_and_extended_bytes() {
+        assert_eq!(ascii_codewords(b"A1"), [66, 50]);
+        assert_eq!(ascii_codewords(b"12a3"), [142, 98, 52]);
+        assert_eq!(ascii_codewords(&[0xE9]), [235, 106]);
+    }
+
+    #[test]
+    fn pads_with_randomised_codewords() {
+        let symbol = DataMatrix::encode(b"A", 12, 12).unwrap();
+        assert_eq!((symbol.rows(), symbol.cols()), (10, 10));
+        let mut codewords = vec![66, PAD];
+        // 129 + (149 * 3) % 253 + 1 - 254
+        codewords.push(70);
+        let padded = DataMatrix::with_size(vec![66], &SIZES[0]);
+        let expected = DataMatrix::with_size(codewords, &SIZES[0]);
+        assert!(padded.modules == expected.modules);
+    }
+
+    #[test]
+    fn picks_the_largest_modules_that_fit() {
+        // 10x10 plus the quiet zone, 3 pixels a module
+        let symbol = DataMatrix::encode(b"123456", 36, 40).unwrap();
+        assert_eq!(symbol.rows(), 10);
+        // too long for 10x10, the next square size is 12x12
+        let symbol = DataMatrix::encode(b"ABCD", 100, 100).unwrap();
+        assert_eq!(symbol.rows(), 12);
+        assert!(DataMatrix::encode(b"123456", 11, 11).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Tag drivers and the write sequence they share. Has no Flipper dependencies so the host tools
+//! can write the simulated tag the way the app does.
+
+use crate::tag::TagSize;
+use crate::transport::{TagInfo, Transport};
+
+mod waveshare;
+
+pub enum Status {
+    SettingUp,
+    Chunk(usize, usize),
+    Finishing,
+    Done,
+}
+
+/// Receives progress while a tag is written, so the same write can drive the GUI or console.
+pub trait Progress {
+    fn update(&mut self, status: Status);
+}
+
+/// Counts of what was sent during a write, for the history log.
+#[derive(Default)]
+pub struct WriteStats {
+    pub chunks: usize,
+    pub retries: u32,
+}
+
+pub enum WriteError {
+    Command,
+    Data,
+    Refresh,
+    Unsupported,
+    NoTag,
+}
+
+impl WriteError {
+    /// Short name for logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Data => "data",
+            Self::Refresh => "refresh",
+            Self::Unsupported => "unsupported",
+            Self::NoTag => "no tag",
+        }
+    }
+}
+
+/// A family of passive NFC e-paper tags and the commands to put an image on them.
+pub trait EpaperTag: Sync {
+    fn name(&self) -> &'static str;
+
+    /// True if the tag found during anticollision is one this driver can write.
+    fn detect(&self, tag: &TagInfo) -> bool;
+
+    /// Wakes the panel and configures it for the panel size.
+    fn setup(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError>;
+
+    /// Sends the image, one packed 1 bit per pixel plane per color the panel shows.
+    fn upload_planes(
+        &self,
+        link: &mut Link,
+        tag_size: TagSize,
+        planes: &[&[u8]],
+        progress: &mut dyn Progress,
+    ) -> Result<(), WriteError>;
+
+    /// Starts showing the uploaded image.
+    fn refresh(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError>;
+
+    /// Waits for the refresh to finish.
+    fn wait(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError>;
+
+    fn power_off(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError>;
+}
+
+static DRIVERS: [&dyn EpaperTag; 1] = [&waveshare::Waveshare];
+
+/// Finds the driver for a detected tag.
+pub fn find(tag: &TagInfo) -> Option<&'static dyn EpaperTag> {
+    DRIVERS.iter().find(|driver| driver.detect(tag)).copied()
+}
+
+/// Timeouts and retries a link applies, taken from the settings.
+#[derive(Clone, Copy)]
+pub struct Timing {
+    /// ms to wait for a response to each frame
+    pub tx_timeout: u32,
+    /// times to resend a frame the tag didn't acknowledge
+    pub retries: u32,
+    /// ms to wait for the panel to finish refreshing
+    pub refresh_timeout: u32,
+}
+
+/// A transport with the timeout and retry settings applied, counting what is sent.
+pub struct Link<'a> {
+    transport: &'a mut dyn Transport,
+    timing: Timing,
+    pub stats: WriteStats,
+    rx: [u8; 32],
+}
+
+impl<'a> Link<'a> {
+    pub fn new(transport: &'a mut dyn Transport, timing: Timing) -> Self {
+        Link {
+            transport,
+            timing,
+            stats: WriteStats::default(),
+            rx: [0; 32],
+        }
+    }
+
+    /// Sends a frame once, returning the response or `None` if the tag didn't answer.
+    pub fn exchange(&mut self, tx: &[u8]) -> Option<&[u8]> {
+        let bits = self
+            .transport
+            .tx_rx(tx, &mut self.rx, self.timing.tx_timeout)?;
+        Some(&self.rx[0..(bits / 8).min(self.rx.len())])
+    }
+
+    /// Sends a frame until the tag answers with `expected`, resending up to the retry setting.
+    pub fn send(&mut self, tx: &[u8], expected: &[u8]) -> bool {
+        for attempt in 0..=self.timing.retries {
+            if attempt > 0 {
+                self.stats.retries += 1;
+            }
+            if self.exchange(tx) == Some(expected) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn delay_ms(&mut self, ms: u32) {
+        self.transport.delay_ms(ms);
+    }
+
+    /// How long to wait for the panel to refresh, in ms.
+    pub fn refresh_timeout(&self) -> u32 {
+        self.timing.refresh_timeout
+    }
+}
+
+/// Puts an image on a detected tag and waits for the panel to refresh.
+pub fn write(
+    driver: &dyn EpaperTag,
+    link: &mut Link,
+    tag_size: TagSize,
+    planes: &[&[u8]],
+    progress: &mut dyn Progress,
+) -> Result<(), WriteError> {
+    progress.update(Status::SettingUp);
+    driver.setup(link, tag_size)?;
+    driver.upload_planes(link, tag_size, planes, progress)?;
+    driver.refresh(link, tag_size)?;
+    progress.update(Status::Finishing);
+    driver.wait(link, tag_size)?;
+    progress.update(Status::Done);
+    driver.power_off(link, tag_size)
+}
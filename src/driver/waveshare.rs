@@ -0,0 +1,91 @@
+use super::{EpaperTag, Link, Progress, Status, WriteError};
+use crate::tag::TagSize;
+use crate::transport::TagInfo;
+
+/// Waveshare NFC-powered e-Paper tags, using the 0xCD commands in `tag.rs`.
+pub struct Waveshare;
+
+const ACK: [u8; 2] = [0x00, 0x00];
+const READY: [u8; 2] = [0xFF, 0x00];
+
+impl Waveshare {
+    fn send_all(&self, link: &mut Link, cmds: &[&[u8]]) -> Result<(), WriteError> {
+        for cmd in cmds {
+            if !link.send(cmd, &ACK) {
+                return Err(WriteError::Command);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EpaperTag for Waveshare {
+    fn name(&self) -> &'static str {
+        "Waveshare"
+    }
+
+    fn detect(&self, tag: &TagInfo) -> bool {
+        tag.uid().starts_with(b"WSDZ10m")
+    }
+
+    fn setup(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError> {
+        for cmd in tag_size.setup() {
+            self.send_all(link, &[&cmd])?;
+        }
+        Ok(())
+    }
+
+    fn upload_planes(
+        &self,
+        link: &mut Link,
+        tag_size: TagSize,
+        planes: &[&[u8]],
+        progress: &mut dyn Progress,
+    ) -> Result<(), WriteError> {
+        // black and white panels only
+        let [plane] = planes else {
+            return Err(WriteError::Unsupported);
+        };
+
+        let (mut buffer, preamble) = tag_size.buffer();
+        let loops = tag_size.loops();
+        for (i, chunk) in tag_size.chunks(plane).enumerate() {
+            for (dst, src) in buffer[preamble..].iter_mut().zip(chunk) {
+                *dst = src;
+            }
+            if !link.send(&buffer, &ACK) {
+                return Err(WriteError::Data);
+            }
+            link.stats.chunks += 1;
+
+            progress.update(Status::Chunk(i + 1, loops));
+        }
+        Ok(())
+    }
+
+    fn refresh(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError> {
+        self.send_all(link, &[&tag_size.power_on(), &tag_size.refresh()])
+    }
+
+    fn wait(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError> {
+        let cmd = tag_size.wait();
+        let mut i = 0;
+        loop {
+            let Some(response) = link.exchange(&cmd) else {
+                return Err(WriteError::Command);
+            };
+            if response == READY {
+                return Ok(());
+            }
+            link.delay_ms(100);
+            i += 100;
+            if i > link.refresh_timeout() {
+                return Err(WriteError::Refresh);
+            }
+        }
+    }
+
+    fn power_off(&self, link: &mut Link, tag_size: TagSize) -> Result<(), WriteError> {
+        self.send_all(link, &[&tag_size.power_off()])
+    }
+}
@@ -0,0 +1,159 @@
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::hex;
+use crate::history;
+use crate::sim::{self, Entry, LoggedTag};
+use crate::update_widget;
+
+const MAX_FRAME_BYTES: usize = 128;
+
+/// Touched only by the listener callback while the listener runs.
+struct State {
+    tag: LoggedTag,
+    start_tick: u32,
+    tx: *mut sys::BitBuffer,
+    widget: *mut sys::Widget,
+}
+
+impl State {
+    fn handle(&mut self, frame: &[u8]) -> Option<&'static [u8]> {
+        let was_shown = self.tag.tag.shown().is_some();
+        let response = self.tag.handle(frame, history::elapsed_ms(self.start_tick));
+        if !was_shown && self.tag.tag.shown().is_some() {
+            update_widget(self.widget, c_string!("image received"));
+        }
+        response
+    }
+}
+
+unsafe extern "C" fn listener_callback(
+    event: sys::NfcGenericEvent,
+    context: *mut c_void,
+) -> sys::NfcCommand {
+    let state = &mut *(context as *mut State);
+    let listener_event = &*(event.event_data as *const sys::Iso14443_3aListenerEvent);
+    if listener_event.type_
+        != sys::Iso14443_3aListenerEventType_Iso14443_3aListenerEventTypeReceivedStandardFrame
+    {
+        return sys::NfcCommand_NfcCommandContinue;
+    }
+
+    let rx = (*listener_event.data).buffer;
+    let frame = core::slice::from_raw_parts(
+        sys::bit_buffer_get_data(rx),
+        sys::bit_buffer_get_size_bytes(rx),
+    );
+    if let Some(response) = state.handle(frame) {
+        sys::bit_buffer_copy_bytes(state.tx, response.as_ptr(), response.len());
+        sys::iso14443_3a_listener_send_standard_frame(
+            event.instance as *mut sys::Iso14443_3aListener,
+            state.tx,
+        );
+    }
+    sys::NfcCommand_NfcCommandContinue
+}
+
+/// One line per command, `<ms> > <frame> < <response>`, and one per run of data chunks.
+fn format_log(log: &[Entry]) -> FuriString {
+    let mut text = FuriString::new();
+    for entry in log {
+        match entry {
+            Entry::Command {
+                ms,
+                frame,
+                response,
+            } => {
+                let _ = uwrite!(text, "{} > ", ms);
+                hex::encode(frame, " ", &mut text);
+                let _ = uwrite!(text, " < ");
+                match response {
+                    Some(response) => hex::encode(response, " ", &mut text),
+                    None => {
+                        let _ = uwrite!(text, "-");
+                    }
+                }
+                let _ = uwrite!(text, "\n");
+            }
+            Entry::Chunks(count) => {
+                let _ = uwrite!(text, "{} data chunks\n", count);
+            }
+        }
+    }
+    text
+}
+
+/// Answers readers as a Waveshare tag, using the same model as the host tools, so the vendor's
+/// app can be watched writing an image.
+pub struct Emulator {
+    nfc: *mut sys::Nfc,
+    listener: *mut sys::NfcListener,
+    data: *mut sys::Iso14443_3aData,
+    state: Box<State>,
+}
+
+impl Emulator {
+    /// Starts answering as the tag, showing on `widget` when an image is received.
+    pub fn start(widget: *mut sys::Widget) -> Self {
+        unsafe {
+            let data = sys::iso14443_3a_alloc();
+            sys::iso14443_3a_set_uid(data, sim::UID.as_ptr(), sim::UID.len());
+            sys::iso14443_3a_set_atqa(data, sim::ATQA.as_ptr());
+            sys::iso14443_3a_set_sak(data, sim::SAK);
+
+            let mut state = Box::new(State {
+                tag: LoggedTag::new(),
+                start_tick: sys::furi_get_tick(),
+                tx: sys::bit_buffer_alloc(MAX_FRAME_BYTES),
+                widget,
+            });
+            let nfc = sys::nfc_alloc();
+            let listener = sys::nfc_listener_alloc(
+                nfc,
+                sys::NfcProtocol_NfcProtocolIso14443_3a,
+                data as *const sys::NfcDeviceData,
+            );
+            sys::nfc_listener_start(
+                listener,
+                Some(listener_callback),
+                &mut *state as *mut State as *mut c_void,
+            );
+            Self {
+                nfc,
+                listener,
+                data,
+                state,
+            }
+        }
+    }
+
+    /// Stops answering and saves the command log to emulated.txt and, if a refresh finished,
+    /// the image the panel would show to emulated.pbm.
+    pub fn stop(self) {
+        unsafe {
+            sys::nfc_listener_stop(self.listener);
+            sys::nfc_listener_free(self.listener);
+            sys::nfc_free(self.nfc);
+            sys::iso14443_3a_free(self.data);
+            sys::bit_buffer_free(self.state.tx);
+        }
+
+        let mut tag = self.state.tag;
+        tag.flush_chunks();
+        let log = format_log(&tag.log);
+        if !files::write("emulated.txt", log.as_c_str().to_bytes()) {
+            println!("couldn't save command log");
+        }
+        if let Some(pbm) = tag.tag.pbm() {
+            if !files::write("emulated.pbm", &pbm) {
+                println!("couldn't save image");
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use core::mem::MaybeUninit;
+use flipperzero::furi::string::FuriString;
+use flipperzero::io::*;
+use flipperzero::storage::{File, OpenOptions};
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::settings::app_data_dir;
+
+/// Path of a file in the app data folder on the SD card.
+pub fn app_data_path(name: &str) -> FuriString {
+    let mut path = FuriString::new();
+    let _ = uwrite!(path, "/ext/apps_data/waveshare/{}", name);
+    path
+}
+
+/// Reads a whole file from the SD card, returning `None` if it can't be opened or read.
+pub fn read_to_end(path: &CStr) -> Option<Vec<u8>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .open_existing(true)
+        .open(path)
+        .ok()?;
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 256];
+    loop {
+        let len = file.read(&mut buffer).ok()?;
+        if len == 0 {
+            return Some(data);
+        }
+        data.extend_from_slice(&buffer[..len]);
+    }
+}
+
+fn make_app_data_dir() {
+    unsafe {
+        let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+        sys::storage_simply_mkdir(storage, app_data_dir());
+        sys::furi_record_close(c_string!("storage"));
+    }
+}
+
+/// Appends to a file in the app data folder, creating the folder and file if needed.
+pub fn append(name: &str, data: &[u8]) -> bool {
+    make_app_data_dir();
+    let Ok(mut file) = OpenOptions::new()
+        .write(true)
+        .open_append(true)
+        .open(app_data_path(name).as_c_str()) else {
+            return false;
+        };
+    matches!(file.write(data), Ok(len) if len == data.len())
+}
+
+/// Creates or truncates a file in the app data folder for writing, creating the folder if needed.
+pub fn create(name: &str) -> Option<File> {
+    make_app_data_dir();
+    OpenOptions::new()
+        .write(true)
+        .create_always(true)
+        .open(app_data_path(name).as_c_str())
+        .ok()
+}
+
+/// Replaces a file in the app data folder, creating the folder if needed.
+pub fn write(name: &str, data: &[u8]) -> bool {
+    let Some(mut file) = create(name) else {
+        return false;
+    };
+    matches!(file.write(data), Ok(len) if len == data.len())
+}
+
+/// Reads at most the last `max` bytes of a file, starting at a line boundary.
+pub fn read_tail(path: &CStr, max: usize) -> Option<Vec<u8>> {
+    unsafe {
+        let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+        let file = sys::storage_file_alloc(storage);
+        let mut data = None;
+        if sys::storage_file_open(
+            file,
+            path.as_ptr(),
+            sys::FS_AccessMode_FSAM_READ,
+            sys::FS_OpenMode_FSOM_OPEN_EXISTING,
+        ) {
+            let size = sys::storage_file_size(file) as usize;
+            let start = size.saturating_sub(max);
+            let mut buffer = vec![0u8; size - start];
+            if sys::storage_file_seek(file, start as u32, true)
+                && sys::storage_file_read(file, buffer.as_mut_ptr() as *mut _, buffer.len() as u16)
+                    as usize
+                    == buffer.len()
+            {
+                if start > 0 {
+                    // drop the partial first line
+                    let skip = buffer.iter().position(|&byte| byte == b'\n').map_or(0, |i| i + 1);
+                    buffer.drain(..skip);
+                }
+                data = Some(buffer);
+            }
+            sys::storage_file_close(file);
+        }
+        sys::storage_file_free(file);
+        sys::furi_record_close(c_string!("storage"));
+        data
+    }
+}
+
+/// Calls `line` with each line of a file without reading it all into memory, so large files like
+/// fonts can be parsed. Returns false if the file can't be read.
+pub fn for_each_line(path: &CStr, mut line: impl FnMut(&str)) -> bool {
+    lines_from(path, 0, |_, text| {
+        line(text);
+        true
+    })
+}
+
+/// Calls `line` with the byte offset and text of each line from byte `start` on, until it returns
+/// false. The offsets let a line be read again later without reading the ones before it.
+pub fn lines_from(path: &CStr, start: usize, mut line: impl FnMut(usize, &str) -> bool) -> bool {
+    let Ok(mut file) = OpenOptions::new().read(true).open_existing(true).open(path) else {
+        return false;
+    };
+    if start > 0 && file.seek(SeekFrom::Start(start as u64)).is_err() {
+        return false;
+    }
+    let mut current = Vec::new();
+    let (mut offset, mut position) = (start, start);
+    let mut buffer = [0u8; 256];
+    loop {
+        let Ok(len) = file.read(&mut buffer) else {
+            return false;
+        };
+        if len == 0 {
+            break;
+        }
+        for &byte in &buffer[..len] {
+            position += 1;
+            if byte == b'\n' {
+                if !line(offset, core::str::from_utf8(&current).unwrap_or("").trim_end()) {
+                    return true;
+                }
+                current.clear();
+                offset = position;
+            } else {
+                current.push(byte);
+            }
+        }
+    }
+    if !current.is_empty() {
+        line(offset, core::str::from_utf8(&current).unwrap_or("").trim_end());
+    }
+    true
+}
+
+/// Names of the files in an app data subfolder ending in `extension`, sorted.
+pub fn names_in(folder: &str, extension: &str) -> Vec<FuriString> {
+    let mut names = Vec::new();
+    unsafe {
+        let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+        let dir = sys::storage_file_alloc(storage);
+        if sys::storage_dir_open(dir, app_data_path(folder).as_c_str().as_ptr()) {
+            let mut info = MaybeUninit::<sys::FileInfo>::zeroed().assume_init();
+            let mut name = [0 as c_char; 256];
+            while sys::storage_dir_read(dir, &mut info, name.as_mut_ptr(), name.len() as u16) {
+                let Ok(name) = CStr::from_ptr(name.as_ptr()).to_str() else {
+                    continue;
+                };
+                if sys::file_info_is_dir(&info) || !name.ends_with(extension) {
+                    continue;
+                }
+                let mut owned = FuriString::new();
+                let _ = uwrite!(owned, "{}", name);
+                names.push(owned);
+            }
+            sys::storage_dir_close(dir);
+        }
+        sys::storage_file_free(dir);
+        sys::furi_record_close(c_string!("storage"));
+    }
+    names.sort_by(|a, b| a.as_c_str().cmp(b.as_c_str()));
+    names
+}
+
+/// Iterates the lines of a text file, skipping blank lines and `#` comments.
+pub fn lines(data: &[u8]) -> impl Iterator<Item = &str> {
+    data.split(|&byte| byte == b'\n')
+        .filter_map(|line| core::str::from_utf8(line).ok())
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
@@ -0,0 +1,25 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use ufmt::uwrite;
+
+use crate::bdf::{BdfFont, Parser};
+use crate::files;
+
+/// App data subfolder the fonts are read from.
+pub const FOLDER: &str = "fonts";
+
+/// Reads `name` from the fonts folder, keeping the glyphs `wanted` accepts and the replacement.
+pub fn load(name: &str, wanted: impl Fn(char) -> bool) -> Option<BdfFont> {
+    let mut path = files::app_data_path(FOLDER);
+    let _ = uwrite!(path, "/{}", name);
+    let mut parser = Parser::new(wanted);
+    if !files::for_each_line(path.as_c_str(), |line| parser.line(line)) {
+        return None;
+    }
+    parser.finish()
+}
+
+/// Font files in the fonts folder.
+pub fn list() -> Vec<FuriString> {
+    files::names_in(FOLDER, ".bdf")
+}
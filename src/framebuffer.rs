@@ -0,0 +1,287 @@
+//! Drawing surface for generated tag content. Has no Flipper dependencies so the host tools can
+//! build it too.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+use crate::tag::TagSize;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    // must match location in menu
+    None = 0,
+    Cw90 = 1,
+    Cw180 = 2,
+    Cw270 = 3,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [Self::None, Self::Cw90, Self::Cw180, Self::Cw270];
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn text(&self) -> &'static CStr {
+        match self {
+            Self::None => c"0",
+            Self::Cw90 => c"90",
+            Self::Cw180 => c"180",
+            Self::Cw270 => c"270",
+        }
+    }
+
+    /// True if the image is turned on its side, swapping width and height.
+    pub fn is_sideways(&self) -> bool {
+        matches!(self, Self::Cw90 | Self::Cw270)
+    }
+}
+
+/// Maps a pixel of the source image to its location on the panel.
+pub fn rotate(
+    rotation: Rotation,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> (usize, usize) {
+    match rotation {
+        Rotation::None => (x, y),
+        Rotation::Cw90 => (height - 1 - y, x),
+        Rotation::Cw180 => (width - 1 - x, height - 1 - y),
+        Rotation::Cw270 => (y, width - 1 - x),
+    }
+}
+
+/// A canvas the size of a panel, 1 bit per pixel, rows packed MSB first with 1 = black like
+/// `Image`. Coordinates outside the panel are clipped, so shapes can hang off the edges.
+pub struct Framebuffer {
+    tag_size: TagSize,
+    data: Vec<u8>,
+}
+
+impl Framebuffer {
+    /// A white canvas for the panel.
+    pub fn new(tag_size: TagSize) -> Self {
+        Self {
+            tag_size,
+            data: vec![0; tag_size.width() * tag_size.height() / 8],
+        }
+    }
+
+    pub fn tag_size(&self) -> TagSize {
+        self.tag_size
+    }
+
+    pub fn width(&self) -> usize {
+        self.tag_size.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tag_size.height()
+    }
+
+    /// Byte index and bit mask of a pixel, `None` if it's off the panel.
+    fn position(&self, x: i32, y: i32) -> Option<(usize, u8)> {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        Some((y * (self.width() / 8) + x / 8, 0x80 >> (x % 8)))
+    }
+
+    /// True if the pixel is black, pixels off the panel are white.
+    pub fn pixel(&self, x: i32, y: i32) -> bool {
+        self.position(x, y)
+            .is_some_and(|(index, mask)| self.data[index] & mask != 0)
+    }
+
+    pub fn set_pixel(&mut self, x: i32, y: i32, black: bool) {
+        if let Some((index, mask)) = self.position(x, y) {
+            if black {
+                self.data[index] |= mask;
+            } else {
+                self.data[index] &= !mask;
+            }
+        }
+    }
+
+    pub fn fill(&mut self, black: bool) {
+        self.data.fill(if black { 0xFF } else { 0x00 });
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, black: bool) {
+        for row in y..y.saturating_add(height as i32) {
+            for column in x..x.saturating_add(width as i32) {
+                self.set_pixel(column, row, black);
+            }
+        }
+    }
+
+    /// Copies a bitmap with its top left corner at `x`, `y`. The bitmap is packed like the
+    /// framebuffer, each row padded to a whole byte.
+    pub fn blit(&mut self, x: i32, y: i32, width: usize, height: usize, bitmap: &[u8]) {
+        let stride = width.div_ceil(8);
+        for (row, bytes) in bitmap.chunks(stride).take(height).enumerate() {
+            for column in 0..width {
+                let black = bytes[column / 8] & (0x80 >> (column % 8)) != 0;
+                self.set_pixel(x + column as i32, y + row as i32, black);
+            }
+        }
+    }
+
+    /// Swaps black and white.
+    pub fn invert(&mut self) {
+        for byte in self.data.iter_mut() {
+            *byte = !*byte;
+        }
+    }
+
+    /// Packed rows, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks(self.width() / 8)
+    }
+
+    /// The data of each chunk command, exactly as the Waveshare driver sends it.
+    pub fn chunks(&self) -> impl Iterator<Item = impl Iterator<Item = u8> + '_> + '_ {
+        self.tag_size.chunks(&self.data)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blit_clips_at_the_edges() {
+        let mut framebuffer = Framebuffer::new(TagSize::TwoNine);
+        // 10x2, rows padded to 2 bytes
+        let bitmap = [0b1100_0000, 0b0100_0000, 0b0000_0000, 0b1100_0000];
+        framebuffer.blit(-1, 294, 10, 2, &bitmap);
+        assert!(framebuffer.pixel(0, 294));
+        assert!(!framebuffer.pixel(1, 294));
+        assert!(framebuffer.pixel(8, 294));
+        assert!(framebuffer.pixel(7, 295));
+        assert!(framebuffer.pixel(8, 295));
+        let black = framebuffer
+            .data()
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum::<u32>();
+        assert_eq!(black, 4);
+
+        // white pixels of the bitmap are copied too
+        framebuffer.fill(true);
+        framebuffer.blit(0, 0, 10, 2, &bitmap);
+        assert!(framebuffer.pixel(0, 0));
+        assert!(!framebuffer.pixel(2, 0));
+        assert!(framebuffer.pixel(10, 0));
+    }
+
+    #[test]
+    fn invert_swaps_every_pixel() {
+        let mut framebuffer = Framebuffer::new(TagSize::FourTwo);
+        framebuffer.set_pixel(3, 7, true);
+        framebuffer.invert();
+        assert!(!framebuffer.pixel(3, 7));
+        assert!(framebuffer.pixel(4, 7));
+        let white = framebuffer
+            .data()
+            .iter()
+            .map(|byte| byte.count_zeros())
+            .sum::<u32>();
+        assert_eq!(white, 1);
+    }
+
+    #[test]
+    fn rotate_turns_clockwise() {
+        // a 296x128 image on the 128x296 panel, or 128x296 for the upright turns
+        let cases = [
+            (Rotation::None, 128, 296, (0, 0), (0, 0)),
+            (Rotation::None, 128, 296, (5, 9), (5, 9)),
+            (Rotation::Cw90, 296, 128, (0, 0), (127, 0)),
+            (Rotation::Cw90, 296, 128, (295, 127), (0, 295)),
+            (Rotation::Cw180, 128, 296, (0, 0), (127, 295)),
+            (Rotation::Cw180, 128, 296, (5, 9), (122, 286)),
+            (Rotation::Cw270, 296, 128, (0, 0), (0, 295)),
+            (Rotation::Cw270, 296, 128, (295, 127), (127, 0)),
+        ];
+        for (rotation, width, height, (x, y), expected) in cases {
+            assert_eq!(rotate(rotation, x, y, width, height), expected);
+            assert_eq!(rotation.is_sideways(), width > height);
+        }
+    }
+
+    #[test]
+    fn rotated_pixels_land_on_the_panel() {
+        for rotation in Rotation::ALL {
+            let (width, height) = if rotation.is_sideways() {
+                (296, 128)
+            } else {
+                (128, 296)
+            };
+            let mut framebuffer = Framebuffer::new(TagSize::TwoNine);
+            for (x, y) in [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ] {
+                let (x, y) = rotate(rotation, x, y, width, height);
+                framebuffer.set_pixel(x as i32, y as i32, true);
+            }
+            // the corners of the image are the corners of the panel
+            for (x, y) in [(0, 0), (127, 0), (0, 295), (127, 295)] {
+                assert!(framebuffer.pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_match_the_tag_layout() {
+        for tag_size in TagSize::ALL {
+            let mut framebuffer = Framebuffer::new(tag_size);
+            let (right, bottom) = (tag_size.width() as i32 - 1, tag_size.height() as i32 - 1);
+            framebuffer.set_pixel(0, 0, true);
+            framebuffer.set_pixel(right, bottom, true);
+
+            let chunks: Vec<Vec<u8>> = framebuffer.chunks().map(Iterator::collect).collect();
+            assert_eq!(chunks.len(), tag_size.loops());
+            assert!(chunks
+                .iter()
+                .all(|chunk| chunk.len() == tag_size.chunk_size() as usize));
+            // inverted, the panel takes 1 = white
+            assert_eq!(chunks[0][0], 0x7F);
+            assert_eq!(*chunks.last().unwrap().last().unwrap(), 0xFE);
+            let white: Vec<u8> = chunks.concat();
+            assert_eq!(white.len(), framebuffer.data().len());
+            assert!(white[1..white.len() - 1].iter().all(|&byte| byte == 0xFF));
+        }
+    }
+
+    #[test]
+    fn chunks_fill_the_data_command() {
+        let mut framebuffer = Framebuffer::new(TagSize::TwoNine);
+        framebuffer.fill_rect(8, 1, 8, 1, true);
+        let (mut buffer, preamble) = TagSize::TwoNine.buffer();
+        assert_eq!(buffer[..preamble], [0xCD, 0x08, 16]);
+
+        // 2.9" rows are 16 bytes, so chunk n is row n
+        let chunk = framebuffer.chunks().nth(1).unwrap();
+        for (dst, src) in buffer[preamble..].iter_mut().zip(chunk) {
+            *dst = src;
+        }
+        let mut expected = vec![0xCD, 0x08, 16, 0xFF, 0x00];
+        expected.resize(3 + 16, 0xFF);
+        assert_eq!(buffer, expected);
+    }
+}
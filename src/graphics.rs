@@ -0,0 +1,180 @@
+//! embedded-graphics support for the panel framebuffers, so labels can be composed from its
+//! shapes, fonts and image decoders and then written like any image.
+
+use core::convert::Infallible;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+
+use crate::framebuffer::Framebuffer;
+use crate::tag::TagSize;
+
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+/// `BinaryColor::On` is black.
+impl DrawTarget for Framebuffer {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set_pixel(point.x, point.y, color.is_on());
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill(color.is_on());
+        Ok(())
+    }
+}
+
+/// Colors of panels that show red as well as black and white.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TriColor {
+    White,
+    Black,
+    Red,
+}
+
+impl PixelColor for TriColor {
+    type Raw = ();
+}
+
+impl From<BinaryColor> for TriColor {
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::On => Self::Black,
+            BinaryColor::Off => Self::White,
+        }
+    }
+}
+
+/// A canvas for red panels, kept as the black and red planes their drivers upload.
+pub struct TriColorFramebuffer {
+    black: Framebuffer,
+    red: Framebuffer,
+}
+
+impl TriColorFramebuffer {
+    /// A white canvas for the panel.
+    pub fn new(tag_size: TagSize) -> Self {
+        Self {
+            black: Framebuffer::new(tag_size),
+            red: Framebuffer::new(tag_size),
+        }
+    }
+
+    /// The planes to pass to `driver::write`, black then red, for drivers of red panels.
+    pub fn planes(&self) -> [&[u8]; 2] {
+        [self.black.data(), self.red.data()]
+    }
+}
+
+impl OriginDimensions for TriColorFramebuffer {
+    fn size(&self) -> Size {
+        self.black.size()
+    }
+}
+
+impl DrawTarget for TriColorFramebuffer {
+    type Color = TriColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.black
+                .set_pixel(point.x, point.y, color == TriColor::Black);
+            self.red.set_pixel(point.x, point.y, color == TriColor::Red);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.black.fill(color == TriColor::Black);
+        self.red.fill(color == TriColor::Red);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+    fn black_pixels(framebuffer: &Framebuffer) -> u32 {
+        framebuffer
+            .data()
+            .iter()
+            .map(|byte| byte.count_ones())
+            .sum()
+    }
+
+    #[test]
+    fn pixels_are_packed_msb_first() {
+        let mut framebuffer = Framebuffer::new(TagSize::TwoNine);
+        assert_eq!(framebuffer.size(), Size::new(128, 296));
+        let _ = Pixel(Point::new(9, 2), BinaryColor::On).draw(&mut framebuffer);
+        let _ = Pixel(Point::new(127, 295), BinaryColor::On).draw(&mut framebuffer);
+        // 16 bytes a row
+        assert_eq!(framebuffer.data()[2 * 16 + 1], 0b0100_0000);
+        assert_eq!(framebuffer.data()[295 * 16 + 15], 0b0000_0001);
+        assert_eq!(black_pixels(&framebuffer), 2);
+
+        let _ = Pixel(Point::new(9, 2), BinaryColor::Off).draw(&mut framebuffer);
+        assert_eq!(framebuffer.data()[2 * 16 + 1], 0);
+    }
+
+    #[test]
+    fn shapes_are_clipped_to_the_panel() {
+        let mut framebuffer = Framebuffer::new(TagSize::TwoNine);
+        let style = PrimitiveStyle::with_fill(BinaryColor::On);
+        // 3x3 of each square is on the panel
+        for corner in [Point::new(-2, -2), Point::new(125, 293)] {
+            let _ = Rectangle::new(corner, Size::new(5, 5))
+                .into_styled(style)
+                .draw(&mut framebuffer);
+        }
+        assert_eq!(black_pixels(&framebuffer), 18);
+        assert_eq!(framebuffer.data()[0], 0b1110_0000);
+        assert_eq!(framebuffer.data()[295 * 16 + 15], 0b0000_0111);
+        assert!(framebuffer.pixel(2, 2));
+        assert!(!framebuffer.pixel(3, 2));
+
+        // entirely off the panel
+        let _ = Rectangle::new(Point::new(200, 10), Size::new(5, 5))
+            .into_styled(style)
+            .draw(&mut framebuffer);
+        assert_eq!(black_pixels(&framebuffer), 18);
+
+        let _ = framebuffer.clear(BinaryColor::On);
+        assert!(framebuffer.data().iter().all(|&byte| byte == 0xFF));
+    }
+
+    #[test]
+    fn tri_color_pixels_go_to_their_plane() {
+        let mut framebuffer = TriColorFramebuffer::new(TagSize::TwoNine);
+        let _ = Pixel(Point::new(0, 0), TriColor::Black).draw(&mut framebuffer);
+        let _ = Pixel(Point::new(1, 0), TriColor::Red).draw(&mut framebuffer);
+        let _ = Pixel(Point::new(-1, 0), TriColor::Red).draw(&mut framebuffer);
+        let [black, red] = framebuffer.planes();
+        assert_eq!(black[0], 0b1000_0000);
+        assert_eq!(red[0], 0b0100_0000);
+        assert_eq!(black.len(), red.len());
+
+        // painting a pixel over clears it in the other plane
+        let _ = Pixel(Point::new(0, 0), TriColor::Red).draw(&mut framebuffer);
+        let _ = Pixel(Point::new(1, 0), TriColor::White).draw(&mut framebuffer);
+        let [black, red] = framebuffer.planes();
+        assert_eq!((black[0], red[0]), (0, 0b1000_0000));
+    }
+}
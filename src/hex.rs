@@ -0,0 +1,32 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use ufmt::uwrite;
+
+/// Decodes hex bytes, ignoring spaces and `:` separators, e.g. `CD 0A` or `57:53:44`.
+pub fn decode(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .bytes()
+        .filter(|byte| !matches!(byte, b' ' | b':'))
+        .map(|byte| (byte as char).to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+/// Appends bytes as upper case hex, with an optional separator between bytes.
+pub fn encode(bytes: &[u8], separator: &str, text: &mut FuriString) {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            let _ = uwrite!(text, "{}", separator);
+        }
+        let _ = uwrite!(
+            text,
+            "{}{}",
+            DIGITS[(byte >> 4) as usize] as char,
+            DIGITS[(byte & 0xF) as usize] as char
+        );
+    }
+}
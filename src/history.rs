@@ -0,0 +1,173 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use ufmt::uwrite;
+
+use crate::driver::{WriteError, WriteStats};
+use crate::files;
+use crate::hex;
+use crate::image::Image;
+use crate::tag::TagSize;
+use crate::transport::TagInfo;
+
+const FILE_NAME: &str = "history.csv";
+
+/// How much of the end of the log the history scene reads.
+const TAIL_BYTES: usize = 8 * 1024;
+
+/// One write attempt, appended to the history log as a CSV line:
+/// `time,uid,atqa,sak,panel,hash,chunks,retries,ms,result,file`. The file is last since the
+/// path may contain commas.
+pub struct Entry<'a> {
+    pub tag: &'a TagInfo,
+    pub tag_size: TagSize,
+    pub image: &'a Image,
+    pub stats: &'a WriteStats,
+    pub elapsed_ms: u32,
+    pub result: &'a Result<(), WriteError>,
+}
+
+/// Milliseconds since `start`, a value of `furi_get_tick`.
+pub fn elapsed_ms(start: u32) -> u32 {
+    unsafe {
+        let ticks = sys::furi_get_tick().wrapping_sub(start);
+        (ticks as u64 * 1000 / sys::furi_kernel_get_tick_frequency() as u64) as u32
+    }
+}
+
+impl Entry<'_> {
+    pub fn append(&self) {
+        let mut datetime = sys::DateTime {
+            hour: 0,
+            minute: 0,
+            second: 0,
+            day: 0,
+            month: 0,
+            year: 0,
+            weekday: 0,
+        };
+        unsafe { sys::furi_hal_rtc_get_datetime(&mut datetime) };
+
+        let mut line = FuriString::new();
+        let _ = uwrite!(
+            line,
+            "{}-{}{}-{}{} {}{}:{}{}:{}{},",
+            datetime.year,
+            datetime.month / 10,
+            datetime.month % 10,
+            datetime.day / 10,
+            datetime.day % 10,
+            datetime.hour / 10,
+            datetime.hour % 10,
+            datetime.minute / 10,
+            datetime.minute % 10,
+            datetime.second / 10,
+            datetime.second % 10
+        );
+        hex::encode(self.tag.uid(), "", &mut line);
+        let _ = uwrite!(line, ",");
+        hex::encode(&self.tag.atqa, "", &mut line);
+        let _ = uwrite!(line, ",");
+        hex::encode(&[self.tag.sak], "", &mut line);
+        let _ = uwrite!(line, ",{},", self.tag_size.name());
+        hex::encode(&self.image.hash().to_be_bytes(), "", &mut line);
+        let result = match self.result {
+            Ok(()) => "ok",
+            Err(error) => error.name(),
+        };
+        let _ = uwrite!(
+            line,
+            ",{},{},{},{},{}\n",
+            self.stats.chunks,
+            self.stats.retries,
+            self.elapsed_ms,
+            result,
+            self.image.file_path.as_c_str().to_str().unwrap_or("")
+        );
+
+        if !files::append(FILE_NAME, line.as_c_str().to_bytes()) {
+            println!("couldn't write history");
+        }
+    }
+}
+
+/// The most recent entries of the history log.
+pub struct History {
+    data: Vec<u8>,
+    lines: Vec<(usize, usize)>,
+}
+
+/// Fields of a logged entry, in the order they are written.
+pub struct Fields<'a> {
+    pub time: &'a str,
+    pub uid: &'a str,
+    pub atqa: &'a str,
+    pub sak: &'a str,
+    pub panel: &'a str,
+    pub hash: &'a str,
+    pub chunks: &'a str,
+    pub retries: &'a str,
+    pub elapsed_ms: &'a str,
+    pub result: &'a str,
+    pub file_path: &'a str,
+}
+
+impl<'a> Fields<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut fields = line.splitn(11, ',');
+        Some(Fields {
+            time: fields.next()?,
+            uid: fields.next()?,
+            atqa: fields.next()?,
+            sak: fields.next()?,
+            panel: fields.next()?,
+            hash: fields.next()?,
+            chunks: fields.next()?,
+            retries: fields.next()?,
+            elapsed_ms: fields.next()?,
+            result: fields.next()?,
+            file_path: fields.next()?,
+        })
+    }
+
+    /// File name without its folder.
+    pub fn file_name(&self) -> &'a str {
+        self.file_path.rsplit('/').next().unwrap_or(self.file_path)
+    }
+}
+
+impl History {
+    pub fn load() -> Option<Self> {
+        let data = files::read_tail(files::app_data_path(FILE_NAME).as_c_str(), TAIL_BYTES)?;
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, &byte) in data.iter().enumerate() {
+            if byte == b'\n' {
+                if i > start {
+                    lines.push((start, i));
+                }
+                start = i + 1;
+            }
+        }
+        lines.reverse(); // newest first
+        Some(History { data, lines })
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Entry `index`, counting back from the newest.
+    pub fn get(&self, index: usize) -> Option<Fields> {
+        let &(start, end) = self.lines.get(index)?;
+        Fields::parse(core::str::from_utf8(&self.data[start..end]).ok()?)
+    }
+
+    /// Newest entry for a tag, with the UID in hex as it is logged.
+    pub fn find_uid(&self, uid: &str) -> Option<Fields> {
+        (0..self.len())
+            .filter_map(|index| self.get(index))
+            .find(|fields| fields.uid == uid)
+    }
+}
@@ -0,0 +1,316 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use flipperzero::furi::string::FuriString;
+use flipperzero::io::*;
+use flipperzero::println;
+use flipperzero::storage::{File, OpenOptions};
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::framebuffer::{rotate, Framebuffer, Rotation};
+use crate::settings::Settings;
+use crate::tag::TagSize;
+
+pub enum ImageError {
+    Open,
+    Read,
+    Format,
+    Size,
+}
+
+impl ImageError {
+    pub fn message(&self) -> *const c_char {
+        match self {
+            Self::Open => c_string!("Can't open file"),
+            Self::Read => c_string!("Can't read file"),
+            Self::Format => c_string!("Bad file format"),
+            Self::Size => c_string!("Wrong image size"),
+        }
+    }
+}
+
+/// A 1 bit per pixel image laid out for the panel, rows packed MSB first, 1 = black like PBM.
+pub struct Image {
+    pub file_path: FuriString,
+    /// Panel the image was loaded or drawn for.
+    pub tag_size: TagSize,
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    fn new(file_path: &CStr, tag_size: TagSize) -> Self {
+        let (width, height) = (tag_size.width(), tag_size.height());
+        let mut path = FuriString::new();
+        let _ = uwrite!(path, "{}", file_path.to_str().unwrap_or(""));
+        Image {
+            file_path: path,
+            tag_size,
+            width,
+            height,
+            data: vec![0; (width / 8) * height],
+        }
+    }
+
+    /// Wraps generated content, `name` stands in for the file path in the history log.
+    pub fn from_framebuffer(framebuffer: Framebuffer, name: &str) -> Self {
+        let mut file_path = FuriString::new();
+        let _ = uwrite!(file_path, "{}", name);
+        Image {
+            file_path,
+            tag_size: framebuffer.tag_size(),
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+            data: framebuffer.into_data(),
+        }
+    }
+
+    /// FNV-1a hash of the image as it will be sent, to tell uploads apart.
+    pub fn hash(&self) -> u32 {
+        self.data.iter().fold(0x811c9dc5, |hash, &byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        })
+    }
+
+    fn set_black(&mut self, x: usize, y: usize) {
+        self.data[y * (self.width / 8) + x / 8] |= 0x80 >> (x % 8);
+    }
+}
+
+/// Buffers reads from a file so the header can be parsed a byte at a time.
+struct Reader {
+    file: File,
+    buffer: [u8; 256],
+    pos: usize,
+    len: usize,
+}
+
+impl Reader {
+    fn next(&mut self) -> Result<u8, ImageError> {
+        if self.pos == self.len {
+            self.len = self.file.read(&mut self.buffer).map_err(|_| ImageError::Read)?;
+            self.pos = 0;
+            if self.len == 0 {
+                return Err(ImageError::Format); // file is truncated
+            }
+        }
+        self.pos += 1;
+        Ok(self.buffer[self.pos - 1])
+    }
+
+    fn read_exact(&mut self, dst: &mut [u8]) -> Result<(), ImageError> {
+        for byte in dst.iter_mut() {
+            *byte = self.next()?;
+        }
+        Ok(())
+    }
+
+    /// Reads a decimal header field, skipping whitespace and comments before it.
+    fn number(&mut self) -> Result<usize, ImageError> {
+        let mut byte = self.next()?;
+        loop {
+            match byte {
+                b'#' => {
+                    while byte != b'\n' {
+                        byte = self.next()?;
+                    }
+                }
+                b' ' | b'\t' | b'\r' | b'\n' => byte = self.next()?,
+                _ => break,
+            }
+        }
+        let mut number = 0usize;
+        while byte.is_ascii_digit() {
+            number = number * 10 + (byte - b'0') as usize;
+            byte = self.next()?;
+        }
+        // exactly one whitespace byte ends the field, and the header
+        match byte {
+            b' ' | b'\t' | b'\r' | b'\n' if number > 0 => Ok(number),
+            _ => Err(ImageError::Format),
+        }
+    }
+}
+
+/// Header fields of a PBM or PGM file.
+struct Header {
+    grayscale: bool,
+    width: usize,
+    height: usize,
+    max: usize,
+}
+
+fn open(path: &CStr) -> Result<(Reader, Header), ImageError> {
+    let Ok(file) = OpenOptions::new()
+        .read(true)
+        .open_existing(true)
+        .open(path) else {
+            println!("couldn't open file");
+            return Err(ImageError::Open);
+        };
+    let mut reader = Reader {
+        file,
+        buffer: [0; 256],
+        pos: 0,
+        len: 0,
+    };
+
+    let mut magic = [0u8; 2];
+    reader.read_exact(&mut magic)?;
+    let grayscale = match &magic {
+        b"P4" => false,
+        b"P5" => true,
+        _ => return Err(ImageError::Format),
+    };
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let max = if grayscale { reader.number()? } else { 1 };
+    if max > 255 {
+        return Err(ImageError::Format);
+    }
+    let header = Header {
+        grayscale,
+        width,
+        height,
+        max,
+    };
+    Ok((reader, header))
+}
+
+/// Picks the panel an image was made for from its dimensions, honoring the rotation setting.
+pub fn detect_tag_size(path: &CStr, rotation: Rotation) -> Result<TagSize, ImageError> {
+    let (_, header) = open(path)?;
+    let (width, height) = if rotation.is_sideways() {
+        (header.height, header.width)
+    } else {
+        (header.width, header.height)
+    };
+    TagSize::ALL
+        .into_iter()
+        .find(|tag_size| tag_size.width() == width && tag_size.height() == height)
+        .ok_or(ImageError::Size)
+}
+
+/// Whether a file name has an image extension `load` reads.
+pub fn is_image(name: &CStr) -> bool {
+    let name = name.to_bytes();
+    name.ends_with(b".pbm") || name.ends_with(b".pgm")
+}
+
+/// Loads a binary PBM (P4) or grayscale PGM (P5) file, applying the rotation, dithering and
+/// inversion settings, and checks it fits the selected panel.
+pub fn load(path: &CStr, settings: &Settings) -> Result<Image, ImageError> {
+    let tag_size: TagSize = settings.tag_size;
+    let (mut reader, header) = open(path)?;
+    let Header {
+        grayscale,
+        width,
+        height,
+        max,
+    } = header;
+
+    let (panel_width, panel_height) = if settings.rotation.is_sideways() {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    if panel_width != tag_size.width() || panel_height != tag_size.height() {
+        println!("image is {}x{}", width, height);
+        return Err(ImageError::Size);
+    }
+
+    let mut image = Image::new(path, tag_size);
+    if grayscale {
+        // Floyd-Steinberg error diffusion, with a pixel of padding on each side
+        let mut row = vec![0u8; width];
+        let mut error = vec![0i32; width + 2];
+        let mut next_error = vec![0i32; width + 2];
+        for y in 0..height {
+            reader.read_exact(&mut row)?;
+            for x in 0..width {
+                let level = row[x] as i32 * 255 / max as i32;
+                let value = if settings.dithering { level + error[x + 1] / 16 } else { level };
+                let black = value < 128;
+                if black {
+                    let (dx, dy) = rotate(settings.rotation, x, y, width, height);
+                    image.set_black(dx, dy);
+                }
+                if settings.dithering {
+                    let diff = value - if black { 0 } else { 255 };
+                    error[x + 2] += diff * 7;
+                    next_error[x] += diff * 3;
+                    next_error[x + 1] += diff * 5;
+                    next_error[x + 2] += diff;
+                }
+            }
+            core::mem::swap(&mut error, &mut next_error);
+            next_error.fill(0);
+        }
+    } else {
+        let mut row = vec![0u8; (width + 7) / 8];
+        for y in 0..height {
+            reader.read_exact(&mut row)?;
+            for x in 0..width {
+                if row[x / 8] & (0x80 >> (x % 8)) != 0 {
+                    let (dx, dy) = rotate(settings.rotation, x, y, width, height);
+                    image.set_black(dx, dy);
+                }
+            }
+        }
+    }
+
+    if settings.invert {
+        for byte in image.data.iter_mut() {
+            *byte = !*byte;
+        }
+    }
+
+    Ok(image)
+}
+
+/// A picture placed on a label at its own size, rows packed MSB first, 1 = black.
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    data: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn is_black(&self, x: usize, y: usize) -> bool {
+        self.data[y * self.width.div_ceil(8) + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// Loads a PBM or PGM file of any size as is, grayscale pixels darker than half are black.
+pub fn load_bitmap(path: &CStr) -> Result<Bitmap, ImageError> {
+    let (mut reader, header) = open(path)?;
+    let Header {
+        grayscale,
+        width,
+        height,
+        max,
+    } = header;
+    let stride = width.div_ceil(8);
+    let mut data = vec![0u8; stride * height];
+    if grayscale {
+        let mut row = vec![0u8; width];
+        for y in 0..height {
+            reader.read_exact(&mut row)?;
+            for (x, &level) in row.iter().enumerate() {
+                if (level as usize) * 2 < max {
+                    data[y * stride + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+    } else {
+        reader.read_exact(&mut data)?;
+    }
+    Ok(Bitmap {
+        width,
+        height,
+        data,
+    })
+}
@@ -0,0 +1,176 @@
+//! The INI files templates are written in, and the `{placeholders}` in their values. Has no
+//! Flipper dependencies so the host tools can build it too, `template` reads the files.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// One `[section]` of a file with its `key = value` lines.
+pub struct Section<'a> {
+    pub kind: &'a str,
+    /// Line number of the section header, counting from 1.
+    pub line: usize,
+    pub keys: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Section<'a> {
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.keys.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+
+    pub fn number(&self, key: &str) -> Option<usize> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// A number that may be left out, but must be valid when given.
+    pub fn number_or(&self, key: &str, default: usize) -> Option<usize> {
+        self.get(key)
+            .map_or(Some(default), |value| value.parse().ok())
+    }
+
+    pub fn flag_or(&self, key: &str, default: bool) -> Option<bool> {
+        match self.get(key) {
+            None => Some(default),
+            Some("yes" | "true" | "1") => Some(true),
+            Some("no" | "false" | "0") => Some(false),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Splits `data` into sections, skipping blank and comment lines. Keys are kept whether or not
+/// the reader knows them. Fails with the number of a line that is neither a header nor a key,
+/// or a key before the first header.
+pub fn parse(data: &[u8]) -> Result<Vec<Section<'_>>, usize> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (i, line) in data.split(|&byte| byte == b'\n').enumerate() {
+        let line = core::str::from_utf8(line).unwrap_or("").trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(kind) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            sections.push(Section {
+                kind: kind.trim(),
+                line: i + 1,
+                keys: Vec::new(),
+            });
+            continue;
+        }
+        match (line.split_once('='), sections.last_mut()) {
+            (Some((key, value)), Some(section)) => {
+                section.keys.push((key.trim(), value.trim()));
+            }
+            _ => return Err(i + 1),
+        }
+    }
+    Ok(sections)
+}
+
+/// Names between braces in the text, e.g. `name` in `Hello {name}`. A `{` without a `}` after
+/// it is plain text.
+fn placeholders_in(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+}
+
+/// Placeholder names in the texts, in the order they first appear.
+pub fn placeholders<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in texts.into_iter().flat_map(placeholders_in) {
+        if !names.iter().any(|known| known == name) {
+            names.push(name.into());
+        }
+    }
+    names
+}
+
+/// Replaces each `{name}` with its value, reading braces the same way as `placeholders`.
+pub fn fill<'v>(text: &str, value: &impl Fn(&str) -> &'v str) -> String {
+    let mut parts = text.split('{');
+    let mut filled = String::from(parts.next().unwrap_or(""));
+    for part in parts {
+        match part.split_once('}') {
+            Some((name, rest)) => {
+                filled.push_str(value(name));
+                filled.push_str(rest);
+            }
+            None => {
+                filled.push('{');
+                filled.push_str(part);
+            }
+        }
+    }
+    filled
+}
+
+/// The lines of a value, which are separated by a `\n` escape as a value can't span lines.
+pub fn lines(text: &str) -> Vec<&str> {
+    text.split("\\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(name: &str) -> &'static str {
+        match name {
+            "name" => "Ada",
+            "id" => "42",
+            _ => "?",
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_keys() {
+        let data = b"; badge\n[template]\nsizes = 2.9\n\n[qr]\nx = 4\ncolour = red\nsize=10\n";
+        let sections = parse(data).unwrap();
+        assert_eq!(sections.len(), 2);
+        let qr = &sections[1];
+        assert_eq!((qr.kind, qr.line), ("qr", 5));
+        assert_eq!(qr.get("colour"), Some("red"));
+        assert_eq!(qr.number("x"), Some(4));
+        assert_eq!(qr.number("size"), Some(10));
+        assert_eq!(qr.get("data"), None);
+    }
+
+    #[test]
+    fn rejects_lines_outside_sections() {
+        assert_eq!(parse(b"x = 1\n[text]\n").err(), Some(1));
+        assert_eq!(parse(b"[text]\nx = 1\nhello\n").err(), Some(3));
+    }
+
+    #[test]
+    fn checks_optional_values() {
+        let sections = parse(b"[rect]\nfill = maybe\nthickness = 2px\n").unwrap();
+        assert_eq!(sections[0].flag_or("fill", false), None);
+        assert_eq!(sections[0].flag_or("caption", true), Some(true));
+        assert_eq!(sections[0].number_or("thickness", 1), None);
+        assert_eq!(sections[0].number_or("width", 1), Some(1));
+    }
+
+    #[test]
+    fn fills_repeated_placeholders() {
+        let text = "{name} #{id}, call me {name}";
+        assert_eq!(placeholders([text, "{id}"]), ["name", "id"]);
+        assert_eq!(fill(text, &value), "Ada #42, call me Ada");
+    }
+
+    #[test]
+    fn keeps_braces_without_a_close() {
+        assert_eq!(placeholders(["a {b", "{x {name}"]), ["name"]);
+        assert_eq!(fill("a {b", &value), "a {b");
+        assert_eq!(fill("{x {name} }", &value), "{x Ada }");
+        assert_eq!(fill("} {}", &value), "} ?");
+    }
+
+    #[test]
+    fn splits_lines_at_escapes() {
+        let sections = parse(b"[text]\ntext = {name}\\n{id}\\n\n").unwrap();
+        let text = fill(sections[0].get("text").unwrap(), &value);
+        assert_eq!(lines(&text), ["Ada", "42", ""]);
+        assert_eq!(lines("one line"), ["one line"]);
+    }
+}
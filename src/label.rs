@@ -0,0 +1,409 @@
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::ffi::{c_char, CStr};
+use embedded_graphics::mono_font::ascii::{FONT_10X20, FONT_4X6, FONT_6X10, FONT_7X13, FONT_9X15};
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder};
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::bdf::{BdfFont, BdfTextStyle};
+use crate::fonts;
+use crate::framebuffer::{rotate, Framebuffer, Rotation};
+use crate::settings::Settings;
+
+/// Room for a typed line, including the nul.
+pub const INPUT_SIZE: usize = 48;
+
+pub const MAX_LINES: usize = 6;
+
+/// Fonts the layout picks from, each drawn up to `MAX_SCALE` times its size for large panels.
+const FONTS: [&MonoFont; 5] = [&FONT_4X6, &FONT_6X10, &FONT_7X13, &FONT_9X15, &FONT_10X20];
+const FONT_NAMES: [&str; 5] = ["4x6", "6x10", "7x13", "9x15", "10x20"];
+const MAX_SCALE: usize = 8;
+
+#[derive(Clone, Copy)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Left => Self::Center,
+            Self::Center => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Self::Left),
+            "center" => Some(Self::Center),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> *const c_char {
+        match self {
+            Self::Left => c_string!("Align: Left"),
+            Self::Center => c_string!("Align: Center"),
+            Self::Right => c_string!("Align: Right"),
+        }
+    }
+}
+
+/// Lines of text typed on the Flipper, laid out to fill the panel.
+pub struct Label {
+    pub input: [c_char; INPUT_SIZE],
+    lines: Vec<CString>,
+    pub align: Align,
+    /// Font files found on the SD card, with `font` 0 for the built-in fonts and 1 for the first.
+    fonts: Vec<FuriString>,
+    font: usize,
+}
+
+impl Label {
+    pub fn new() -> Self {
+        Self {
+            input: [0; INPUT_SIZE],
+            lines: Vec::new(),
+            align: Align::Center,
+            fonts: Vec::new(),
+            font: 0,
+        }
+    }
+
+    /// Lists the fonts on the SD card again, keeping the selected one if it's still there.
+    pub fn find_fonts(&mut self) {
+        let selected = self.font.checked_sub(1).map(|i| self.fonts[i].clone());
+        self.fonts = fonts::list();
+        self.font = selected
+            .and_then(|selected| {
+                self.fonts
+                    .iter()
+                    .position(|font| font.as_c_str() == selected.as_c_str())
+            })
+            .map_or(0, |i| i + 1);
+    }
+
+    pub fn next_font(&mut self) {
+        self.font = (self.font + 1) % (self.fonts.len() + 1);
+    }
+
+    pub fn font_text(&self) -> FuriString {
+        let mut text = FuriString::new();
+        let _ = match self.font.checked_sub(1) {
+            Some(i) => uwrite!(
+                text,
+                "Font: {}",
+                self.fonts[i].as_c_str().to_str().unwrap_or("?")
+            ),
+            None => uwrite!(text, "Font: Built-in"),
+        };
+        text
+    }
+
+    pub fn lines(&self) -> &[CString] {
+        &self.lines
+    }
+
+    /// Puts line `index` in the input to be edited, or clears it for a new line.
+    pub fn edit(&mut self, index: usize) {
+        self.input = [0; INPUT_SIZE];
+        if let Some(line) = self.lines.get(index) {
+            for (dst, src) in self.input.iter_mut().zip(line.as_bytes()) {
+                *dst = *src as c_char;
+            }
+        }
+    }
+
+    /// Stores the input as line `index`, an empty input removes the line.
+    pub fn save(&mut self, index: usize) {
+        let input = unsafe { CStr::from_ptr(self.input.as_ptr()) };
+        if input.is_empty() {
+            if index < self.lines.len() {
+                self.lines.remove(index);
+            }
+        } else if index < self.lines.len() {
+            self.lines[index] = input.into();
+        } else if self.lines.len() < MAX_LINES {
+            self.lines.push(input.into());
+        }
+    }
+
+    /// Draws the lines as large as they fit on the panel, turned by the rotation setting.
+    pub fn render(&self, settings: &Settings) -> Result<Framebuffer, *const c_char> {
+        let mut framebuffer = Framebuffer::new(settings.tag_size);
+        let mut canvas = Canvas::new(&mut framebuffer, settings.rotation);
+        let (width, height) = (canvas.width, canvas.height);
+        let margin = width.min(height) / 20;
+        let lines: Vec<&str> = self
+            .lines
+            .iter()
+            .map(|line| line.to_str().unwrap_or(""))
+            .collect();
+        // only the glyphs of the label are kept, a whole font may not fit in memory
+        let bdf = match self.font.checked_sub(1) {
+            Some(i) => {
+                let name = self.fonts[i].as_c_str().to_str().unwrap_or("");
+                let font = fonts::load(name, |c| lines.iter().any(|line| line.contains(c)));
+                Some(font.ok_or(c_string!("Can't load font"))?)
+            }
+            None => None,
+        };
+        let faces: Vec<Face> = match &bdf {
+            Some(font) => alloc::vec![Face::Bdf(font)],
+            None => builtin_faces(),
+        };
+        let area = Area {
+            left: margin,
+            top: margin,
+            width: width - 2 * margin,
+            height: height - 2 * margin,
+        };
+        draw_text(&mut canvas, &faces, &lines, self.align, &area);
+
+        if settings.invert {
+            framebuffer.invert();
+        }
+        Ok(framebuffer)
+    }
+}
+
+/// Part of the panel in label coordinates.
+pub struct Area {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Wraps and draws the text as large as it fits in the area, centered vertically.
+pub fn draw_text(canvas: &mut Canvas, faces: &[Face], text: &[&str], align: Align, area: &Area) {
+    let layout = layout(faces, text, area.width, area.height);
+    let scale = layout.scale;
+    let line_height = layout.face.line_height();
+    let (x, alignment) = match align {
+        Align::Left => (area.left, Alignment::Left),
+        Align::Center => (area.left + area.width / 2, Alignment::Center),
+        Align::Right => (area.left + area.width, Alignment::Right),
+    };
+    let used = layout.lines.len() * line_height * scale;
+    let top = (area.top + area.height.saturating_sub(used) / 2) / scale;
+    let text_style = TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(Baseline::Top)
+        .build();
+
+    canvas.scale = scale;
+    for (i, line) in layout.lines.iter().enumerate() {
+        let position = Point::new((x / scale) as i32, (top + i * line_height) as i32);
+        match layout.face {
+            Face::Mono(font) => {
+                let style = MonoTextStyle::new(font, BinaryColor::On);
+                draw(line, position, style, text_style, canvas);
+            }
+            Face::Bdf(font) => {
+                let style = BdfTextStyle::new(font, BinaryColor::On);
+                draw(line, position, style, text_style, canvas);
+            }
+        }
+    }
+    canvas.scale = 1;
+}
+
+fn draw<S>(line: &str, position: Point, style: S, text_style: TextStyle, canvas: &mut Canvas)
+where
+    S: TextRenderer<Color = BinaryColor>,
+{
+    let _ = Text::with_text_style(line, position, style, text_style).draw(canvas);
+}
+
+pub fn builtin_faces() -> Vec<Face<'static>> {
+    FONTS.iter().map(|&font| Face::Mono(font)).collect()
+}
+
+/// The built-in font named by its glyph size, e.g. `6x10`.
+pub fn builtin_face(name: &str) -> Option<Face<'static>> {
+    let index = FONT_NAMES.iter().position(|&font| font == name)?;
+    Some(Face::Mono(FONTS[index]))
+}
+
+/// A built-in font or one loaded from the SD card.
+#[derive(Clone, Copy)]
+pub enum Face<'a> {
+    Mono(&'static MonoFont<'static>),
+    Bdf(&'a BdfFont),
+}
+
+impl Face<'_> {
+    fn line_height(&self) -> usize {
+        match self {
+            Self::Mono(font) => font.character_size.height as usize,
+            Self::Bdf(font) => font.line_height() as usize,
+        }
+    }
+
+    /// Width of the text in pixels before scaling.
+    fn width(&self, text: &str) -> usize {
+        match self {
+            Self::Mono(font) => {
+                text.chars().count() * (font.character_size.width + font.character_spacing) as usize
+            }
+            Self::Bdf(font) => font.width(text) as usize,
+        }
+    }
+}
+
+/// Breaks text into lines at most `width` pixels wide, at spaces where possible.
+fn wrap(face: Face, text: &str, width: usize, lines: &mut Vec<String>) {
+    let mut line = String::new();
+    for mut word in text.split(' ').filter(|word| !word.is_empty()) {
+        loop {
+            let len = if line.is_empty() {
+                face.width(word)
+            } else {
+                face.width(&line) + face.width(" ") + face.width(word)
+            };
+            if len <= width {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                break;
+            }
+            if !line.is_empty() {
+                lines.push(core::mem::take(&mut line));
+                continue;
+            }
+            // wider than a whole line, split the word keeping at least one character
+            let split = word
+                .char_indices()
+                .skip(1)
+                .map(|(i, _)| i)
+                .take_while(|&i| face.width(&word[..i]) <= width)
+                .last()
+                .unwrap_or_else(|| word.chars().next().map_or(word.len(), char::len_utf8));
+            lines.push(word[..split].into());
+            word = &word[split..];
+            if word.is_empty() {
+                break;
+            }
+        }
+    }
+    lines.push(line);
+}
+
+struct Layout<'a> {
+    face: Face<'a>,
+    scale: usize,
+    lines: Vec<String>,
+}
+
+/// Picks the largest face and scale the wrapped text fits with, or the smallest if none do.
+fn layout<'a>(faces: &[Face<'a>], text: &[&str], width: usize, height: usize) -> Layout<'a> {
+    let mut best: Option<Layout> = None;
+    for scale in 1..=MAX_SCALE {
+        for &face in faces {
+            let line_height = face.line_height() * scale;
+            let mut lines = Vec::new();
+            for line in text {
+                wrap(face, line, width / scale, &mut lines);
+            }
+            let larger = best.as_ref().map_or(true, |best| {
+                line_height > best.face.line_height() * best.scale
+            });
+            if lines.len() * line_height <= height && larger {
+                best = Some(Layout { face, scale, lines });
+            }
+        }
+    }
+    best.unwrap_or_else(|| {
+        let face = faces[0];
+        let mut lines = Vec::new();
+        for line in text {
+            wrap(face, line, width, &mut lines);
+        }
+        Layout {
+            face,
+            scale: 1,
+            lines,
+        }
+    })
+}
+
+/// Draws in label coordinates, scaled up and turned onto the panel.
+pub struct Canvas<'a> {
+    framebuffer: &'a mut Framebuffer,
+    rotation: Rotation,
+    /// Size of the panel as seen after the rotation.
+    pub width: usize,
+    pub height: usize,
+    scale: usize,
+}
+
+impl<'a> Canvas<'a> {
+    pub fn new(framebuffer: &'a mut Framebuffer, rotation: Rotation) -> Self {
+        let tag_size = framebuffer.tag_size();
+        let (width, height) = if rotation.is_sideways() {
+            (tag_size.height(), tag_size.width())
+        } else {
+            (tag_size.width(), tag_size.height())
+        };
+        Self {
+            framebuffer,
+            rotation,
+            width,
+            height,
+            scale: 1,
+        }
+    }
+}
+
+impl OriginDimensions for Canvas<'_> {
+    fn size(&self) -> Size {
+        Size::new(
+            (self.width / self.scale) as u32,
+            (self.height / self.scale) as u32,
+        )
+    }
+}
+
+impl DrawTarget for Canvas<'_> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            for dy in 0..self.scale {
+                for dx in 0..self.scale {
+                    let (x, y) = (x * self.scale + dx, y * self.scale + dy);
+                    if x >= self.width || y >= self.height {
+                        continue;
+                    }
+                    let (x, y) = rotate(self.rotation, x, y, self.width, self.height);
+                    self.framebuffer
+                        .set_pixel(x as i32, y as i32, color.is_on());
+                }
+            }
+        }
+        Ok(())
+    }
+}
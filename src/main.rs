@@ -1,5 +1,6 @@
-//! Template project for Flipper Zero.
-//! This app prints "Hello, Rust!" to the console then exits.
+//! Writes images to Waveshare NFC-powered e-Paper tags with a Flipper Zero. Images come from PBM
+//! or PGM files, or are generated as text, QR code, barcode and template labels. Tags can be
+//! written one at a time, in batches or from the CLI.
 
 #![no_main]
 #![no_std]
@@ -9,22 +10,69 @@ extern crate alloc;
 extern crate flipperzero_alloc;
 extern crate flipperzero_rt;
 
-use flipperzero::dialogs::{DialogFileBrowserOptions, DialogsApp};
 use flipperzero::furi::string::FuriString;
-use flipperzero::io::*;
-use flipperzero::storage::{File, OpenOptions};
 use flipperzero::println;
 use flipperzero_rt::{entry, manifest};
 use flipperzero_sys as sys;
 
 use alloc::boxed::Box;
-use core::ffi::{c_char, c_void, CStr};
-use core::ptr::{null_mut, NonNull};
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_void};
+use core::ptr::{null, NonNull};
 use sys::c_string;
 use ufmt::uwrite;
 
+mod args;
+mod barcode;
+mod barcode_label;
+mod batch;
+mod bdf;
+mod cache;
+mod capture;
+mod cli;
+mod console;
+mod datamatrix;
+mod driver;
+mod emulate;
+mod files;
+mod fonts;
+mod framebuffer;
+mod graphics;
+mod hex;
+mod history;
+mod image;
+mod ini;
+mod label;
+mod mapping;
+mod merge;
+mod pdf417;
+mod poller;
+mod qr;
+mod qr_label;
+mod scan;
+mod scenes;
+mod settings;
+mod sim;
 mod tag;
-use tag::TagSize;
+mod template;
+mod transport;
+mod writer;
+use args::{Launch, OpenArgs};
+use barcode_label::BarcodeLabel;
+use batch::Batch;
+use cache::Cache;
+use console::Console;
+use emulate::Emulator;
+use history::History;
+use image::Image;
+use label::Label;
+use mapping::Mappings;
+use qr_label::QrLabel;
+use scan::Scanner;
+use scenes::{AppEvent, Scene};
+use settings::Settings;
+use template::Form;
+use transport::TagInfo;
 
 // Define the FAP Manifest for this application
 manifest!(
@@ -38,62 +86,140 @@ manifest!(
 entry!(main);
 
 enum AppView {
-    VariableItemList = 0,
-    Widget = 1,
-}
-
-enum AppEvent {
-    SetTagSize(TagSize),
-    OpenImage,
-    WriteTag,
-    WaitForTag,
-}
-
-impl AppEvent {
-    pub fn to_int(self) -> u32 {
-        match self {
-            Self::OpenImage => 1, // needs to match location in menu
-            Self::WriteTag => 2,  // needs to match location in menu
-            Self::WaitForTag => 3,
-            Self::SetTagSize(TagSize::TwoNine) => 4,
-            Self::SetTagSize(TagSize::FourTwo) => 5,
-            Self::SetTagSize(TagSize::SevenFive) => 6,
-        }
-    }
+    Submenu = 0,
+    VariableItemList = 1,
+    Widget = 2,
+    DialogEx = 3,
+    TextBox = 4,
+    TextInput = 5,
 }
 
 struct App {
     view_dispatcher: NonNull<sys::ViewDispatcher>,
+    scene_manager: NonNull<sys::SceneManager>,
+    submenu: NonNull<sys::Submenu>,
     variable_item_list: NonNull<sys::VariableItemList>,
     widget: NonNull<sys::Widget>,
-    tag_size: TagSize,
-    tag_size_menu_item: Option<NonNull<sys::VariableItem>>,
-    file_path: Option<FuriString>,
-    file_menu_item: Option<NonNull<sys::VariableItem>>,
-    file: Option<File>,
+    dialog_ex: NonNull<sys::DialogEx>,
+    text_box: NonNull<sys::TextBox>,
+    text_input: NonNull<sys::TextInput>,
+    settings: Settings,
+    image: Option<Image>,
+    batch: Option<Batch>,
+    mappings: Option<Mappings>,
+    tag: Option<TagInfo>,
+    history: Option<History>,
+    cache: Cache,
+    console: Console,
+    scanner: Scanner,
+    emulator: Option<Emulator>,
+    label: Label,
+    qr: QrLabel,
+    barcode: BarcodeLabel,
+    /// Files in the templates folder, and the one being filled in.
+    templates: Vec<FuriString>,
+    form: Option<Form>,
+    /// CSV file a template is merged with, picked before the template.
+    csv_path: Option<FuriString>,
+    error_message: *const c_char,
+    text: FuriString, // dialog_ex and text_box don't copy their strings
 }
 
 impl App {
-    pub fn new() -> Self {
-        App {
+    pub fn new() -> Box<Self> {
+        let mut app = Box::new(App {
             view_dispatcher: unsafe { NonNull::new_unchecked(sys::view_dispatcher_alloc()) },
+            scene_manager: NonNull::dangling(), // allocated below, needs a stable app pointer
+            submenu: unsafe { NonNull::new_unchecked(sys::submenu_alloc()) },
             variable_item_list: unsafe { NonNull::new_unchecked(sys::variable_item_list_alloc()) },
             widget: unsafe { NonNull::new_unchecked(sys::widget_alloc()) },
-            tag_size: TagSize::TwoNine,
-            tag_size_menu_item: None,
-            file_path: None,
-            file_menu_item: None,
-            file: None,
+            dialog_ex: unsafe { NonNull::new_unchecked(sys::dialog_ex_alloc()) },
+            text_box: unsafe { NonNull::new_unchecked(sys::text_box_alloc()) },
+            text_input: unsafe { NonNull::new_unchecked(sys::text_input_alloc()) },
+            settings: Settings::load(),
+            image: None,
+            batch: None,
+            mappings: None,
+            tag: None,
+            history: None,
+            cache: Cache::load(),
+            console: Console::new(),
+            scanner: Scanner::new(),
+            emulator: None,
+            label: Label::new(),
+            qr: QrLabel::new(),
+            barcode: BarcodeLabel::new(),
+            templates: Vec::new(),
+            form: None,
+            csv_path: None,
+            error_message: null(),
+            text: FuriString::new(),
+        });
+        let context = &mut *app as *mut App as *mut c_void;
+        app.scene_manager =
+            unsafe { NonNull::new_unchecked(sys::scene_manager_alloc(scenes::handlers(), context)) };
+        app
+    }
+
+    pub fn send_event(&self, event: AppEvent) {
+        unsafe { sys::view_dispatcher_send_custom_event(self.view_dispatcher.as_ptr(), event.into()) }
+    }
+
+    pub fn switch_to_view(&self, view: AppView) {
+        unsafe { sys::view_dispatcher_switch_to_view(self.view_dispatcher.as_ptr(), view as u32) }
+    }
+
+    pub fn next_scene(&self, scene: Scene) {
+        unsafe { sys::scene_manager_next_scene(self.scene_manager.as_ptr(), scene as u32) }
+    }
+
+    pub fn back_to_scene(&self, scene: Scene) -> bool {
+        unsafe {
+            sys::scene_manager_search_and_switch_to_previous_scene(
+                self.scene_manager.as_ptr(),
+                scene as u32,
+            )
         }
     }
+
+    /// Goes back to where the image being written came from.
+    pub fn back_to_source(&self) -> bool {
+        Scene::SOURCES.iter().any(|&scene| self.back_to_scene(scene))
+    }
+
+    pub fn set_scene_state(&self, scene: Scene, state: u32) {
+        unsafe { sys::scene_manager_set_scene_state(self.scene_manager.as_ptr(), scene as u32, state) }
+    }
+
+    pub fn scene_state(&self, scene: Scene) -> u32 {
+        unsafe { sys::scene_manager_get_scene_state(self.scene_manager.as_ptr(), scene as u32) }
+    }
+
+    pub fn show_error(&mut self, message: *const c_char) {
+        self.error_message = message;
+        self.next_scene(Scene::Error);
+    }
 }
 
 impl Drop for App {
     fn drop(&mut self) {
         unsafe {
-            sys::view_dispatcher_free(self.view_dispatcher.as_ptr());
+            let view_dispatcher = self.view_dispatcher.as_ptr();
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::Submenu as u32);
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::VariableItemList as u32);
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::Widget as u32);
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::DialogEx as u32);
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::TextBox as u32);
+            sys::view_dispatcher_remove_view(view_dispatcher, AppView::TextInput as u32);
+            sys::view_dispatcher_free(view_dispatcher);
+            sys::scene_manager_free(self.scene_manager.as_ptr());
+            sys::submenu_free(self.submenu.as_ptr());
             sys::variable_item_list_free(self.variable_item_list.as_ptr());
             sys::widget_free(self.widget.as_ptr());
+            sys::dialog_ex_free(self.dialog_ex.as_ptr());
+            sys::text_box_free(self.text_box.as_ptr());
+            sys::text_input_free(self.text_input.as_ptr());
+            sys::furi_record_close(c_string!("gui"));
         }
     }
 }
@@ -113,248 +239,31 @@ fn update_widget(widget: *mut sys::Widget, message: *const c_char) {
     }
 }
 
-pub unsafe extern "C" fn item_enter_callback(context: *mut c_void, index: u32) {
-    println!("item enter callback index {}", index);
+pub unsafe extern "C" fn custom_event_callback(context: *mut c_void, event: u32) -> bool {
     let app = context as *mut App;
-    match index {
-        i if i == AppEvent::OpenImage.to_int() => {
-            println!("open image selected");
-            sys::view_dispatcher_send_custom_event(
-                (*app).view_dispatcher.as_ptr(),
-                AppEvent::OpenImage.to_int(),
-            );
-        }
-        i if i == AppEvent::WriteTag.to_int() => {
-            println!("write tag selected");
-            sys::view_dispatcher_send_custom_event(
-                (*app).view_dispatcher.as_ptr(),
-                AppEvent::WriteTag.to_int(),
-            );
-        }
-        _ => println!("unknown item enter index {}", index),
-    }
-}
-
-pub unsafe extern "C" fn set_tag_size_callback(item: *mut sys::VariableItem) {
-    let index = sys::variable_item_get_current_value_index(item);
-    let app = sys::variable_item_get_context(item) as *mut App;
-    let view_dispatcher = (*app).view_dispatcher.as_ptr();
-
-    match index {
-        i if i == TagSize::TwoNine as u8 => {
-            sys::view_dispatcher_send_custom_event(
-                view_dispatcher,
-                AppEvent::SetTagSize(TagSize::TwoNine).to_int(),
-            );
-        }
-        i if i == TagSize::FourTwo as u8 => {
-            sys::view_dispatcher_send_custom_event(
-                view_dispatcher,
-                AppEvent::SetTagSize(TagSize::FourTwo).to_int(),
-            );
-        }
-
-        i if i == TagSize::SevenFive as u8 => {
-            sys::view_dispatcher_send_custom_event(
-                view_dispatcher,
-                AppEvent::SetTagSize(TagSize::SevenFive).to_int(),
-            );
-        }
-
-        _ => println!("unknown menu item index {}", index),
-    }
+    sys::scene_manager_handle_custom_event((*app).scene_manager.as_ptr(), event)
 }
 
-fn do_variable_item_list(app: *const App) {
-    unsafe {
-        let app = app as *mut App;
-        let variable_item_list = (*app).variable_item_list.as_ptr();
-
-        sys::variable_item_list_set_enter_callback(
-            variable_item_list,
-            Some(item_enter_callback),
-            app as *mut c_void,
-        );
-
-        let item = sys::variable_item_list_add(
-            variable_item_list,
-            c_string!("Tag Size"),
-            3,
-            Some(set_tag_size_callback),
-            app as *mut c_void,
-        );
-        (*app).tag_size_menu_item = Some(NonNull::new_unchecked(item));
-
-        sys::variable_item_set_current_value_index(item, (*app).tag_size as u8);
-        sys::variable_item_set_current_value_text(item, (*app).tag_size.text());
-
-        let item = sys::variable_item_list_add(
-            variable_item_list,
-            c_string!("Open Image"),
-            1,
-            None,
-            null_mut(),
-        );
-        (*app).file_menu_item = Some(NonNull::new_unchecked(item));
-
-        sys::variable_item_set_current_value_text(item, c_string!("None"));
-
-        let _ = sys::variable_item_list_add(
-            variable_item_list,
-            c_string!("Write Tag"),
-            1,
-            None,
-            null_mut(),
-        );
-    }
-}
-
-pub unsafe extern "C" fn custom_event_callback(context: *mut c_void, event: u32) -> bool {
-    println!("custom event callback");
+pub unsafe extern "C" fn navigation_event_callback(context: *mut c_void) -> bool {
+    println!("navigation event callback");
     let app = context as *mut App;
-    match event {
-        evt if evt == AppEvent::SetTagSize(TagSize::TwoNine).to_int() => {
-            println!("2.9 tag size selected");
-            (*app).tag_size = TagSize::TwoNine;
-            if let Some(item) = (*app).tag_size_menu_item {
-                sys::variable_item_set_current_value_text(item.as_ptr(), TagSize::TwoNine.text())
-            }
-        }
-        evt if evt == AppEvent::SetTagSize(TagSize::FourTwo).to_int() => {
-            println!("4.2 tag size selected");
-            (*app).tag_size = TagSize::FourTwo;
-            if let Some(item) = (*app).tag_size_menu_item {
-                sys::variable_item_set_current_value_text(item.as_ptr(), TagSize::FourTwo.text())
-            }
-        }
-        evt if evt == AppEvent::SetTagSize(TagSize::SevenFive).to_int() => {
-            println!("7.5 tag size selected");
-            (*app).tag_size = TagSize::SevenFive;
-            if let Some(item) = (*app).tag_size_menu_item {
-                sys::variable_item_set_current_value_text(item.as_ptr(), TagSize::SevenFive.text())
-            }
-        }
-        evt if evt == AppEvent::OpenImage.to_int() => {
-            println!("open image event received");
-            let mut dialogs_app = DialogsApp::open();
-            let file_browser_options = DialogFileBrowserOptions::new()
-                .set_hide_dot_files(true)
-                .set_extension(CStr::from_bytes_until_nul(b"pbm\0").unwrap())
-                .set_hide_ext(false);
-            (*app).file_path = dialogs_app.show_file_browser(None, Some(&file_browser_options));
-            match &(*app).file_path {
-                Some(file_path) => {
-                    println!("file selected {}", file_path);
-                    if let Some(item) = (*app).file_menu_item {
-                        sys::variable_item_set_current_value_text(
-                            item.as_ptr(),
-                            file_path.as_c_str().as_ptr(),
-                        );
-                    }
-                }
-                None => {
-                    println!("no file selected");
-                    if let Some(item) = (*app).file_menu_item {
-                        sys::variable_item_set_current_value_text(item.as_ptr(), c_string!("None"));
-                    }
-                }
-            };
-        }
-        evt if evt == AppEvent::WriteTag.to_int() => {
-            println!("write tag event received");
-            if let Some(file_path) = &(*app).file_path {
-                sys::view_dispatcher_switch_to_view(
-                    (*app).view_dispatcher.as_ptr(),
-                    AppView::Widget as u32,
-                );
-
-                if sys::furi_hal_nfc_is_busy() {
-                    println!("nfc is busy");
-                    update_widget((*app).widget.as_ptr(), c_string!("Can't start NFC"));
-                    return true;
-                }
-
-                let Ok(mut file) = OpenOptions::new()
-                    .read(true)
-                    .open_existing(true)
-                    .open(file_path.as_c_str()) else {
-                        println!("couldn't open file");
-                        update_widget((*app).widget.as_ptr(), c_string!("Can't open file"));
-                        return true;
-                    };
-
-                let header = (*app).tag_size.header();
-                let mut buffer: [u8; 11] = Default::default();
-                let Ok(_) = file.read(&mut buffer) else {
-                        println!("couldn't read from file");
-                        update_widget((*app).widget.as_ptr(), c_string!("Can't read file"));
-                        return true;
-                };
-
-                if &buffer != header {
-                    println!("file header doesn't match");
-                    update_widget((*app).widget.as_ptr(), c_string!("Bad file format"));
-                    return true;
-                }
-
-                (*app).file = Some(file);
-
-                update_widget((*app).widget.as_ptr(), c_string!("waiting for tag"));
-                sys::view_dispatcher_send_custom_event(
-                    (*app).view_dispatcher.as_ptr(),
-                    AppEvent::WaitForTag.to_int(),
-                );
-            }
-        }
-        evt if evt == AppEvent::WaitForTag.to_int() => {
-            let mut dev_data = sys::FuriHalNfcDevData {
-                type_: sys::FuriHalNfcType_FuriHalNfcTypeA,
-                interface: sys::FuriHalNfcInterface_FuriHalNfcInterfaceRf,
-                uid_len: 0,
-                uid: Default::default(),
-                cuid: 0,
-                atqa: Default::default(),
-                sak: 0,
-            };
-
-            sys::furi_hal_nfc_exit_sleep();
-
-            let timeout = 300;
-            if sys::furi_hal_nfc_detect(
-                &mut dev_data as *mut sys::FuriHalNfcDevData,
-                timeout as u32,
-            ) && &dev_data.uid[0..7] == b"WSDZ10m"
-            {
-                println!("found tag");
-                sys::view_dispatcher_stop((*app).view_dispatcher.as_ptr()); // exit back to main()
-                return true;
-            }
-
-            sys::furi_hal_nfc_sleep();
-            sys::furi_delay_ms(50);
-            sys::view_dispatcher_send_custom_event(
-                (*app).view_dispatcher.as_ptr(),
-                AppEvent::WaitForTag.to_int(),
-            ); // run wait for tag event again
-        }
-        _ => println!("unknown app event {}", event),
-    }
-    true
+    // returns false from the root scene, which will cause view dispatcher to stop
+    sys::scene_manager_handle_back_event((*app).scene_manager.as_ptr())
 }
 
-fn do_view_dispatcher(app: *const App) {
+fn do_view_dispatcher(app: *const App, start: Scene) {
     unsafe {
         let view_dispatcher = (*app).view_dispatcher.as_ptr();
+        let submenu = (*app).submenu.as_ptr();
         let variable_item_list = (*app).variable_item_list.as_ptr();
         let widget = (*app).widget.as_ptr();
+        let dialog_ex = (*app).dialog_ex.as_ptr();
+        let text_box = (*app).text_box.as_ptr();
+        let text_input = (*app).text_input.as_ptr();
 
         sys::view_dispatcher_enable_queue(view_dispatcher);
         sys::view_dispatcher_set_event_callback_context(view_dispatcher, app as *mut c_void);
 
-        pub unsafe extern "C" fn navigation_event_callback(_context: *mut c_void) -> bool {
-            println!("navigation event callback");
-            false // will cause view dispatcher to stop
-        }
         sys::view_dispatcher_set_navigation_event_callback(
             view_dispatcher,
             Some(navigation_event_callback),
@@ -365,6 +274,11 @@ fn do_view_dispatcher(app: *const App) {
             Some(custom_event_callback),
         );
 
+        sys::view_dispatcher_add_view(
+            view_dispatcher,
+            AppView::Submenu as u32,
+            sys::submenu_get_view(submenu),
+        );
         sys::view_dispatcher_add_view(
             view_dispatcher,
             AppView::VariableItemList as u32,
@@ -375,6 +289,21 @@ fn do_view_dispatcher(app: *const App) {
             AppView::Widget as u32,
             sys::widget_get_view(widget),
         );
+        sys::view_dispatcher_add_view(
+            view_dispatcher,
+            AppView::DialogEx as u32,
+            sys::dialog_ex_get_view(dialog_ex),
+        );
+        sys::view_dispatcher_add_view(
+            view_dispatcher,
+            AppView::TextBox as u32,
+            sys::text_box_get_view(text_box),
+        );
+        sys::view_dispatcher_add_view(
+            view_dispatcher,
+            AppView::TextInput as u32,
+            sys::text_input_get_view(text_input),
+        );
 
         let gui = sys::furi_record_open(c_string!("gui")) as *mut sys::Gui;
         sys::view_dispatcher_attach_to_gui(
@@ -382,150 +311,61 @@ fn do_view_dispatcher(app: *const App) {
             gui,
             sys::ViewDispatcherType_ViewDispatcherTypeFullscreen,
         );
-        sys::view_dispatcher_switch_to_view(view_dispatcher, AppView::VariableItemList as u32);
+        sys::scene_manager_next_scene((*app).scene_manager.as_ptr(), Scene::MainMenu as u32);
+        if start != Scene::MainMenu {
+            sys::scene_manager_next_scene((*app).scene_manager.as_ptr(), start as u32);
+        }
 
         sys::view_dispatcher_run(view_dispatcher);
     }
 }
 
-fn do_write_tag(file: &mut File, widget: *mut sys::Widget, tag_size: TagSize) -> i32 {
-    unsafe {
-        let timeout = 300;
-        let mut tx_rx = Box::new(sys::FuriHalNfcTxRxContext {
-            tx_data: [0; 512],
-            tx_parity: [0; 64],
-            tx_bits: 0,
-            rx_data: [0; 512],
-            rx_parity: [0; 64],
-            rx_bits: 0,
-            tx_rx_type: sys::FuriHalNfcTxRxType_FuriHalNfcTxRxTypeDefault,
-            nfca_signal: null_mut(),
-            sniff_tx: None,
-            sniff_rx: None,
-            sniff_context: null_mut(),
-        });
-
-        update_widget(widget, c_string!("setting up"));
-
-        for cmd in tag_size.setup() {
-            tx_rx.tx_data[0..cmd.len()].copy_from_slice(&cmd);
-            tx_rx.tx_bits = cmd.len() as u16 * 8;
-            let result =
-                sys::furi_hal_nfc_tx_rx(&mut *tx_rx as *mut sys::FuriHalNfcTxRxContext, timeout);
-            if result == false
-                || tx_rx.rx_bits != 16
-                || tx_rx.rx_data[0] != 0
-                || tx_rx.rx_data[1] != 0
-            {
-                println!("nfc write cmd failure");
-                return -1;
-            }
+/// Applies a file path, and optionally a panel, passed by the launcher. Returns the scene to
+/// start on.
+fn apply_open_args(app: &mut App, args: OpenArgs) -> Scene {
+    println!("launched with {}", args.file_path);
+
+    let mut file_path = FuriString::new();
+    let _ = uwrite!(file_path, "{}", args.file_path);
+    let tag_size = match args.tag_size {
+        Some(tag_size) => Ok(tag_size),
+        None => image::detect_tag_size(file_path.as_c_str(), app.settings.rotation),
+    };
+    app.settings.file_path = Some(file_path);
+    match tag_size {
+        Ok(tag_size) => {
+            app.settings.tag_size = tag_size;
+            Scene::Preview
         }
-
-        let mut progress = FuriString::new();
-        let (mut buffer, preamble) = tag_size.buffer();
-        let loops = tag_size.loops();
-        for i in 1..=loops {
-            let Ok(_) = file.read(&mut buffer[preamble..]) else { return -1; };
-            tx_rx.tx_data[0..preamble].copy_from_slice(&buffer[0..preamble]);
-            for (dst, src) in tx_rx.tx_data[preamble..buffer.len()]
-                .iter_mut()
-                .zip(&buffer[preamble..buffer.len()])
-            {
-                *dst = !(*src);
-            }
-            tx_rx.tx_bits = buffer.len() as u16 * 8;
-            let result =
-                sys::furi_hal_nfc_tx_rx(&mut *tx_rx as *mut sys::FuriHalNfcTxRxContext, timeout);
-
-            if result == false
-                || tx_rx.rx_bits != 16
-                || tx_rx.rx_data[0] != 0
-                || tx_rx.rx_data[1] != 0
-            {
-                println!("nfc write data failure");
-                return -1;
-            }
-
-            progress.clear();
-            let _ = uwrite!(progress, "chunk {}/{}", i, loops).unwrap();
-            update_widget(widget, progress.as_c_str().as_ptr());
-        }
-
-        for cmd in [tag_size.power_on(), tag_size.refresh()] {
-            tx_rx.tx_data[0..cmd.len()].copy_from_slice(&cmd);
-            tx_rx.tx_bits = cmd.len() as u16 * 8;
-            let result =
-                sys::furi_hal_nfc_tx_rx(&mut *tx_rx as *mut sys::FuriHalNfcTxRxContext, timeout);
-            if result == false
-                || tx_rx.rx_bits != 16
-                || tx_rx.rx_data[0] != 0
-                || tx_rx.rx_data[1] != 0
-            {
-                println!("nfc write cmd failure");
-                return -1;
-            }
-        }
-
-        update_widget(widget, c_string!("finishing"));
-
-        let cmd = tag_size.wait();
-        let mut i = 0;
-        loop {
-            tx_rx.tx_data[0..cmd.len()].copy_from_slice(&cmd);
-            tx_rx.tx_bits = cmd.len() as u16 * 8;
-            let result =
-                sys::furi_hal_nfc_tx_rx(&mut *tx_rx as *mut sys::FuriHalNfcTxRxContext, timeout);
-            if result == false {
-                println!("nfc write cmd failure");
-                return -1;
-            }
-            if tx_rx.rx_bits == 16 && tx_rx.rx_data[0] == 0xFF && tx_rx.rx_data[1] == 0x00 {
-                println!("image saved");
-                break;
-            }
-            sys::furi_delay_ms(100);
-            i += 1;
-            if i > 50 {
-                println!("nfc save data failure");
-
-                return -1;
-            }
-        }
-
-        update_widget(widget, c_string!("done!"));
-
-        let cmd = tag_size.power_off();
-        tx_rx.tx_data[0..cmd.len()].copy_from_slice(&cmd);
-        tx_rx.tx_bits = cmd.len() as u16 * 8;
-        let result =
-            sys::furi_hal_nfc_tx_rx(&mut *tx_rx as *mut sys::FuriHalNfcTxRxContext, timeout);
-        if result == false || tx_rx.rx_bits != 16 || tx_rx.rx_data[0] != 0 || tx_rx.rx_data[1] != 0
-        {
-            println!("nfc write cmd failure");
-            return -1;
+        Err(error) => {
+            app.error_message = error.message();
+            Scene::Error
         }
     }
-    0
 }
 
-fn main(_args: *mut u8) -> i32 {
-    let mut app = Box::new(App::new());
-
-    do_variable_item_list(&*app);
+fn main(args: *mut u8) -> i32 {
+    let launch = unsafe { args::parse(args as *const c_char) };
 
-    do_view_dispatcher(&*app);
+    let open_args = match launch {
+        None => None,
+        Some(Ok(Launch::Open(open_args))) => Some(open_args),
+        Some(Ok(Launch::Write(write_args))) => return cli::run(write_args),
+        Some(Err(error)) => {
+            println!("{}", error.message());
+            println!("{}", args::USAGE);
+            return -1;
+        }
+    };
 
-    if app.file.is_none() {
-        return -1;
-    }
+    let mut app = App::new();
 
-    let mut file = app.file.take().unwrap();
+    let start = match open_args {
+        Some(open_args) => apply_open_args(&mut app, open_args),
+        None => Scene::MainMenu,
+    };
 
-    let ret = do_write_tag(&mut file, app.widget.as_ptr(), app.tag_size);
-    unsafe {
-        sys::furi_hal_nfc_sleep();
-    }
+    do_view_dispatcher(&*app, start);
 
-    ret
+    0
 }
@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::framebuffer::Rotation;
+use crate::hex;
+use crate::tag::TagSize;
+
+/// The image assigned to a tag, with optional overrides of the panel and rotation settings.
+pub struct Mapping {
+    pub uid: Vec<u8>,
+    pub file_path: FuriString,
+    pub tag_size: Option<TagSize>,
+    pub rotation: Option<Rotation>,
+}
+
+/// Tags with known images, loaded from `mapping.csv` in the app data folder.
+pub struct Mappings {
+    entries: Vec<Mapping>,
+    pub written: usize,
+}
+
+fn parse_rotation(text: &str) -> Option<Rotation> {
+    match text {
+        "0" => Some(Rotation::None),
+        "90" => Some(Rotation::Cw90),
+        "180" => Some(Rotation::Cw180),
+        "270" => Some(Rotation::Cw270),
+        _ => None,
+    }
+}
+
+/// Parses `uid,path[,panel[,rotation]]`, e.g. `5753445A31306D010203,/ext/labels/a.pbm,4.2,90`.
+fn parse_line(line: &str) -> Option<Mapping> {
+    let mut fields = line.split(',').map(str::trim);
+    let uid = hex::decode(fields.next()?)?;
+    let mut file_path = FuriString::new();
+    let _ = uwrite!(file_path, "{}", fields.next().filter(|path| !path.is_empty())?);
+    let tag_size = match fields.next() {
+        Some("") | None => None,
+        Some(name) => Some(TagSize::from_name(name)?),
+    };
+    let rotation = match fields.next() {
+        Some("") | None => None,
+        Some(text) => Some(parse_rotation(text)?),
+    };
+    Some(Mapping {
+        uid,
+        file_path,
+        tag_size,
+        rotation,
+    })
+}
+
+impl Mappings {
+    pub fn load() -> Option<Self> {
+        let data = files::read_to_end(files::app_data_path("mapping.csv").as_c_str())?;
+        let mut entries = Vec::new();
+        for line in files::lines(&data) {
+            match parse_line(line) {
+                Some(mapping) => entries.push(mapping),
+                None => println!("bad mapping line {}", line),
+            }
+        }
+        if entries.is_empty() {
+            return None;
+        }
+        Some(Mappings {
+            entries,
+            written: 0,
+        })
+    }
+
+    pub fn find(&self, uid: &[u8]) -> Option<&Mapping> {
+        self.entries.iter().find(|mapping| mapping.uid == uid)
+    }
+}
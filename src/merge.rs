@@ -0,0 +1,227 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::framebuffer::Framebuffer;
+use crate::hex;
+use crate::settings::Settings;
+use crate::tag::TagSize;
+use crate::template::Template;
+
+/// Rows of a CSV file filled into a template, one tag each. Written rows are appended to a
+/// progress file in the app data folder, so an interrupted merge continues where it stopped.
+pub struct Merge {
+    template: Template,
+    csv_path: FuriString,
+    /// Progress file name, after the CSV file and template, and the comment line naming them.
+    progress: String,
+    progress_header: String,
+    /// The progress file exists and starts with the comment line.
+    progress_saved: bool,
+    /// Header row, each column fills the placeholder of the same name.
+    columns: Vec<String>,
+    /// Byte offset of each row in the CSV file, so a row is read without the ones before it.
+    offsets: Vec<usize>,
+    written: Vec<bool>,
+    /// Fields of the row loaded last, with its index and key fields.
+    fields: Vec<String>,
+    row: Option<usize>,
+    key: String,
+}
+
+/// Splits a CSV line at commas. Fields may be double quoted to hold commas, with `""` for a quote.
+fn split(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields.iter().map(|field| field.trim().into()).collect()
+}
+
+/// Progress file name for merging a CSV file with a template. Keyed on the full path, so
+/// same-named files in different folders, or the same file with another template, don't share it.
+fn progress_name(csv_path: &str, template_name: &str) -> String {
+    let bytes = csv_path.bytes().chain([0]).chain(template_name.bytes());
+    let hash = bytes.fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    let mut name = FuriString::new();
+    let _ = uwrite!(name, "merge-");
+    hex::encode(&hash.to_be_bytes(), "", &mut name);
+    let _ = uwrite!(name, ".done");
+    name.as_c_str().to_str().unwrap_or("merge.done").into()
+}
+
+impl Merge {
+    /// Loads the template and the CSV header, and checks the template fits the panel and every
+    /// placeholder has a column.
+    pub fn open(
+        template_name: &CStr,
+        csv_path: &CStr,
+        tag_size: TagSize,
+    ) -> Result<Self, *const c_char> {
+        let template = Template::load(template_name.to_str().unwrap_or(""))?;
+        if !template.fits(tag_size) {
+            return Err(c_string!("Template not for this panel"));
+        }
+        let mut header = None;
+        let mut offsets = Vec::new();
+        let read = files::lines_from(csv_path, 0, |offset, line| {
+            if line.is_empty() {
+                return true;
+            }
+            if header.is_none() {
+                header = Some(split(line.trim_start_matches('\u{feff}')));
+            } else {
+                offsets.push(offset);
+            }
+            true
+        });
+        if !read {
+            return Err(c_string!("Can't read CSV file"));
+        }
+        let columns = header.unwrap_or_default();
+        let rows = offsets.len();
+        if rows == 0 {
+            return Err(c_string!("No rows in CSV file"));
+        }
+        for placeholder in template.placeholders() {
+            if !columns.contains(placeholder) {
+                println!("no column for {}", placeholder.as_str());
+                return Err(c_string!("CSV lacks a placeholder"));
+            }
+        }
+
+        let csv_path = csv_path.to_str().unwrap_or("");
+        let template_name = template_name.to_str().unwrap_or("");
+        let progress = progress_name(csv_path, template_name);
+        let mut progress_header = FuriString::new();
+        let _ = uwrite!(progress_header, "# {} {}\n", csv_path, template_name);
+        let mut written = vec![false; rows];
+        let data = files::read_to_end(files::app_data_path(&progress).as_c_str());
+        let progress_saved = data.is_some();
+        if let Some(data) = data {
+            // the comment line naming the CSV file and template is skipped
+            for line in files::lines(&data) {
+                match line.parse::<usize>() {
+                    Ok(row) if (1..=rows).contains(&row) => written[row - 1] = true,
+                    _ => println!("bad progress line {}", line),
+                }
+            }
+        }
+
+        let mut path = FuriString::new();
+        let _ = uwrite!(path, "{}", csv_path);
+        Ok(Self {
+            template,
+            csv_path: path,
+            progress,
+            progress_header: progress_header.as_c_str().to_str().unwrap_or("").into(),
+            progress_saved,
+            columns,
+            offsets,
+            written,
+            fields: Vec::new(),
+            row: None,
+            key: String::new(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.written.len()
+    }
+
+    pub fn is_written(&self, row: usize) -> bool {
+        self.written[row]
+    }
+
+    /// Rows written in this or earlier sessions.
+    pub fn written(&self) -> usize {
+        self.written.iter().filter(|&&written| written).count()
+    }
+
+    /// Forgets the rows written before, to write them all again.
+    pub fn restart(&mut self) {
+        self.written.fill(false);
+        self.progress_saved = files::write(&self.progress, self.progress_header.as_bytes());
+    }
+
+    pub fn mark_written(&mut self, row: usize) {
+        self.written[row] = true;
+        let mut line = FuriString::new();
+        if !self.progress_saved {
+            let _ = uwrite!(line, "{}", self.progress_header.as_str());
+            self.progress_saved = true;
+        }
+        let _ = uwrite!(line, "{}\n", row + 1);
+        if !files::append(&self.progress, line.as_c_str().to_bytes()) {
+            println!("couldn't save merge progress");
+        }
+    }
+
+    /// Reads the fields of a row, counting from the first row after the header.
+    fn load(&mut self, row: usize) -> Result<(), *const c_char> {
+        if self.row == Some(row) {
+            return Ok(());
+        }
+        let mut fields = None;
+        let read = files::lines_from(self.csv_path.as_c_str(), self.offsets[row], |_, line| {
+            if !line.is_empty() {
+                fields = Some(split(line));
+            }
+            false
+        });
+        if !read {
+            return Err(c_string!("Can't read CSV file"));
+        }
+        self.fields = fields.ok_or(c_string!("CSV file changed"))?;
+        self.row = Some(row);
+        // the first two fields tell the tags apart on the screen
+        self.key.clear();
+        for field in self.fields.iter().take(2).filter(|field| !field.is_empty()) {
+            if !self.key.is_empty() {
+                self.key.push(' ');
+            }
+            self.key.push_str(field);
+        }
+        Ok(())
+    }
+
+    /// Key fields of the row rendered last.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn render(
+        &mut self,
+        row: usize,
+        settings: &Settings,
+    ) -> Result<Framebuffer, *const c_char> {
+        self.load(row)?;
+        let value = |name: &str| {
+            let column = self.columns.iter().position(|column| column == name);
+            column
+                .and_then(|column| self.fields.get(column))
+                .map_or("", String::as_str)
+        };
+        self.template.render(value, settings)
+    }
+}
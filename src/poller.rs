@@ -0,0 +1,146 @@
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicBool, Ordering};
+use flipperzero_sys as sys;
+
+use crate::settings::Settings;
+use crate::transport::{TagInfo, Transport};
+
+/// Largest frame sent or received, the 7.5" data chunk plus preamble.
+const MAX_FRAME_BYTES: usize = 128;
+
+/// Carrier cycles per ms at 13.56 MHz, the unit of the frame wait time.
+const FC_PER_MS: u32 = 13_560;
+
+/// Transport over the ISO14443-3A poller, only valid inside the poller callback.
+struct PollerTransport {
+    poller: *mut sys::Iso14443_3aPoller,
+    tx: *mut sys::BitBuffer,
+    rx: *mut sys::BitBuffer,
+    /// Standard frames, or raw ones without a CRC added or checked.
+    crc: bool,
+}
+
+impl Transport for PollerTransport {
+    fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], timeout: u32) -> Option<usize> {
+        unsafe {
+            sys::bit_buffer_copy_bytes(self.tx, tx.as_ptr(), tx.len());
+            let fwt = timeout.saturating_mul(FC_PER_MS);
+            // standard frames have the CRC added to tx and checked and removed from rx
+            let error = if self.crc {
+                sys::iso14443_3a_poller_send_standard_frame(self.poller, self.tx, self.rx, fwt)
+            } else {
+                sys::iso14443_3a_poller_txrx(self.poller, self.tx, self.rx, fwt)
+            };
+            if error != sys::Iso14443_3aError_Iso14443_3aErrorNone {
+                return None;
+            }
+            let len = sys::bit_buffer_get_size_bytes(self.rx).min(rx.len());
+            sys::bit_buffer_write_bytes(self.rx, rx.as_mut_ptr() as *mut c_void, len);
+            Some(sys::bit_buffer_get_size(self.rx))
+        }
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        unsafe { sys::furi_delay_ms(ms) }
+    }
+}
+
+/// Shared between `with_tag` and the poller callback running on the NFC worker thread.
+struct Session<'a> {
+    poller: *mut sys::NfcPoller,
+    ready: *mut sys::FuriSemaphore,
+    run: &'a mut dyn FnMut(&TagInfo, &mut dyn Transport),
+    crc: bool,
+    /// Set once a tag was found, `ready` is released when its session is over.
+    started: AtomicBool,
+    done: bool,
+}
+
+unsafe extern "C" fn poller_callback(
+    event: sys::NfcGenericEvent,
+    context: *mut c_void,
+) -> sys::NfcCommand {
+    let session = &mut *(context as *mut Session);
+    let poller_event = &*(event.event_data as *const sys::Iso14443_3aPollerEvent);
+    if poller_event.type_ != sys::Iso14443_3aPollerEventType_Iso14443_3aPollerEventTypeReady {
+        return sys::NfcCommand_NfcCommandContinue; // keep polling until a tag shows up
+    }
+    session.started.store(true, Ordering::Release);
+
+    let data = &*(sys::nfc_poller_get_data(session.poller) as *const sys::Iso14443_3aData);
+    let tag = TagInfo::new(&data.uid[..data.uid_len as usize], data.atqa, data.sak);
+    let mut transport = PollerTransport {
+        poller: event.instance as *mut sys::Iso14443_3aPoller,
+        tx: sys::bit_buffer_alloc(MAX_FRAME_BYTES),
+        rx: sys::bit_buffer_alloc(MAX_FRAME_BYTES),
+        crc: session.crc,
+    };
+    (session.run)(&tag, &mut transport);
+    sys::bit_buffer_free(transport.tx);
+    sys::bit_buffer_free(transport.rx);
+
+    session.done = true;
+    sys::furi_semaphore_release(session.ready);
+    sys::NfcCommand_NfcCommandStop
+}
+
+/// Waits up to the detect timeout for an NFC-A tag, then runs `run` with the tag selected.
+/// `run` is called on the NFC worker thread and this blocks until it returns. Returns false if
+/// no tag was found.
+pub fn with_tag(settings: &Settings, run: &mut dyn FnMut(&TagInfo, &mut dyn Transport)) -> bool {
+    run_session(settings, true, run)
+}
+
+/// Like `with_tag`, but frames are sent as given and responses returned with their CRC, whether
+/// it's right or not.
+pub fn with_tag_raw(
+    settings: &Settings,
+    run: &mut dyn FnMut(&TagInfo, &mut dyn Transport),
+) -> bool {
+    run_session(settings, false, run)
+}
+
+fn run_session(
+    settings: &Settings,
+    crc: bool,
+    run: &mut dyn FnMut(&TagInfo, &mut dyn Transport),
+) -> bool {
+    unsafe {
+        let nfc = sys::nfc_alloc();
+        let poller = sys::nfc_poller_alloc(nfc, sys::NfcProtocol_NfcProtocolIso14443_3a);
+        let ready = sys::furi_semaphore_alloc(1, 0);
+        let mut session = Session {
+            poller,
+            ready,
+            run,
+            crc,
+            started: AtomicBool::new(false),
+            done: false,
+        };
+
+        sys::nfc_poller_start(
+            poller,
+            Some(poller_callback),
+            &mut session as *mut Session as *mut c_void,
+        );
+        let status =
+            sys::furi_semaphore_acquire(ready, sys::furi_ms_to_ticks(settings.detect_timeout));
+        if status != sys::FuriStatus_FuriStatusOk && session.started.load(Ordering::Acquire) {
+            // the timeout only covers detection, a session that has started runs to the end
+            sys::furi_semaphore_acquire(ready, sys::FuriWait_FuriWaitForever);
+        }
+        sys::nfc_poller_stop(poller);
+
+        sys::furi_semaphore_free(ready);
+        sys::nfc_poller_free(poller);
+        sys::nfc_free(nfc);
+        session.done
+    }
+}
+
+/// Waits up to the detect timeout for any NFC-A tag in the field.
+pub fn detect_any(settings: &Settings) -> Option<TagInfo> {
+    let mut found = None;
+    with_tag(settings, &mut |tag, _| found = Some(*tag));
+    found
+}
@@ -0,0 +1,626 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+pub const MIN_VERSION: u8 = 1;
+pub const MAX_VERSION: u8 = 40;
+
+/// Modules of white space required around a code for scanners to find it.
+pub const QUIET_ZONE: usize = 4;
+
+/// How much of the code can be damaged and still read, roughly 7%, 15%, 25% and 30%.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Ecc {
+    Low = 0,
+    Medium = 1,
+    Quartile = 2,
+    High = 3,
+}
+
+impl Ecc {
+    pub const ALL: [Ecc; 4] = [Self::Low, Self::Medium, Self::Quartile, Self::High];
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Parses the level letter, e.g. `M`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "L" | "l" => Some(Self::Low),
+            "M" | "m" => Some(Self::Medium),
+            "Q" | "q" => Some(Self::Quartile),
+            "H" | "h" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> &'static CStr {
+        match self {
+            Self::Low => c"L 7%",
+            Self::Medium => c"M 15%",
+            Self::Quartile => c"Q 25%",
+            Self::High => c"H 30%",
+        }
+    }
+
+    fn format_bits(&self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium => 0,
+            Self::Quartile => 3,
+            Self::High => 2,
+        }
+    }
+}
+
+// Per error correction level and version, index 0 unused.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+        30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+    [
+        0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30,
+        30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+];
+const ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+        14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+        23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+    [
+        0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29,
+        34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68,
+    ],
+    [
+        0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32,
+        35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81,
+    ],
+];
+
+// Penalty weights for choosing the mask.
+const PENALTY_RUN: i32 = 3;
+const PENALTY_BLOCK: i32 = 3;
+const PENALTY_FINDER: i32 = 40;
+const PENALTY_BALANCE: i32 = 10;
+
+/// A QR code symbol, with the data in byte mode.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes the data in the given version, or the smallest that fits when `None`.
+    pub fn encode(data: &[u8], ecc: Ecc, version: Option<u8>) -> Option<Self> {
+        let versions = match version {
+            Some(version) => version..=version,
+            None => MIN_VERSION..=MAX_VERSION,
+        };
+        let version = versions.into_iter().find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            data.len() < 1 << count_bits
+                && 4 + count_bits + data.len() * 8 <= data_codewords(version, ecc) * 8
+        })?;
+
+        let mut bits = Bits::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+        for &byte in data {
+            bits.push(byte as u32, 8);
+        }
+        let capacity = data_codewords(version, ecc) * 8;
+        bits.push(0, (capacity - bits.len).min(4));
+        bits.push(0, (8 - bits.len % 8) % 8);
+        for pad in [0xEC, 0x11].into_iter().cycle() {
+            if bits.len >= capacity {
+                break;
+            }
+            bits.push(pad, 8);
+        }
+
+        let size = version as usize * 4 + 17;
+        let mut code = Self {
+            size,
+            modules: vec![false; size * size],
+            is_function: vec![false; size * size],
+        };
+        code.draw_function_patterns(version, ecc);
+        code.draw_codewords(&add_ecc_and_interleave(&bits.bytes, version, ecc));
+
+        let mut best = (0, i32::MAX);
+        for mask in 0..8 {
+            code.apply_mask(mask);
+            code.draw_format_bits(ecc, mask);
+            let penalty = code.penalty();
+            if penalty < best.1 {
+                best = (mask, penalty);
+            }
+            code.apply_mask(mask);
+        }
+        code.apply_mask(best.0);
+        code.draw_format_bits(ecc, best.0);
+        Some(code)
+    }
+
+    /// Width and height in modules, without the quiet zone.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: u8, ecc: Ecc) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                    if (0..size as i32).contains(&xx) && (0..size as i32).contains(&yy) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // the corners with finder patterns
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let (xx, yy) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+                        self.set_function(xx, yy, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+
+        // reserved now, filled in once the mask is chosen
+        self.draw_format_bits(ecc, 0);
+
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let dark = bits >> i & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, ecc: Ecc, mask: u8) {
+        let data = ecc.format_bits() << 3 | mask as u32;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 != 0;
+        let size = self.size;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Places the codewords in the zig-zag column pairs from the bottom right.
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.is_function[y * size + x] && i < data.len() * 8 {
+                        self.modules[y * size + x] = data[i / 8] >> (7 - i % 8) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flips the data modules in the mask pattern, applying it twice undoes it.
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let i = y * self.size + x;
+                self.modules[i] ^= invert && !self.is_function[i];
+            }
+        }
+    }
+
+    fn penalty(&self) -> i32 {
+        let size = self.size;
+        let mut result = 0;
+
+        for transpose in [false, true] {
+            let module = |a: usize, b: usize| {
+                if transpose {
+                    self.module(b, a)
+                } else {
+                    self.module(a, b)
+                }
+            };
+            for b in 0..size {
+                let mut color = false;
+                let mut run = 0;
+                let mut history = RunHistory::new(size as i32);
+                for a in 0..size {
+                    if module(a, b) == color {
+                        run += 1;
+                        if run == 5 {
+                            result += PENALTY_RUN;
+                        } else if run > 5 {
+                            result += 1;
+                        }
+                    } else {
+                        history.add(run);
+                        if !color {
+                            result += history.finder_patterns() * PENALTY_FINDER;
+                        }
+                        color = module(a, b);
+                        run = 1;
+                    }
+                }
+                result += history.finish(color, run) * PENALTY_FINDER;
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.module(x, y);
+                if color == self.module(x + 1, y)
+                    && color == self.module(x, y + 1)
+                    && color == self.module(x + 1, y + 1)
+                {
+                    result += PENALTY_BLOCK;
+                }
+            }
+        }
+
+        let dark = self.modules.iter().filter(|&&dark| dark).count() as i32;
+        let total = (size * size) as i32;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        result + k * PENALTY_BALANCE
+    }
+}
+
+/// Lengths of the last runs in a row or column, for spotting finder like patterns.
+struct RunHistory {
+    size: i32,
+    runs: [i32; 7],
+}
+
+impl RunHistory {
+    fn new(size: i32) -> Self {
+        Self { size, runs: [0; 7] }
+    }
+
+    fn add(&mut self, mut run: i32) {
+        // the light border before the first run
+        if self.runs[0] == 0 {
+            run += self.size;
+        }
+        self.runs.copy_within(0..6, 1);
+        self.runs[0] = run;
+    }
+
+    /// Counts 1:1:3:1:1 dark and light runs with four light modules on either side.
+    fn finder_patterns(&self) -> i32 {
+        let runs = &self.runs;
+        let n = runs[1];
+        let core = n > 0 && runs[2] == n && runs[3] == n * 3 && runs[4] == n && runs[5] == n;
+        (core && runs[0] >= n * 4 && runs[6] >= n) as i32
+            + (core && runs[6] >= n * 4 && runs[0] >= n) as i32
+    }
+
+    fn finish(mut self, color: bool, mut run: i32) -> i32 {
+        if color {
+            self.add(run);
+            run = 0;
+        }
+        // the light border after the last run
+        self.add(run + self.size);
+        self.finder_patterns()
+    }
+}
+
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len % 8 == 0 {
+                self.bytes.push(0);
+            }
+            let bit = (value >> i & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+}
+
+fn raw_data_modules(version: u8) -> usize {
+    let version = version as usize;
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: u8, ecc: Ecc) -> usize {
+    let (ecc, version) = (ecc as usize, version as usize);
+    raw_data_modules(version as u8) / 8
+        - ECC_CODEWORDS_PER_BLOCK[ecc][version] as usize
+            * ERROR_CORRECTION_BLOCKS[ecc][version] as usize
+}
+
+fn alignment_positions(version: u8) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let version = version as usize;
+    let count = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+    };
+    let size = version * 4 + 17;
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// Splits the data into blocks, appends each block's error correction and interleaves them.
+fn add_ecc_and_interleave(data: &[u8], version: u8, ecc: Ecc) -> Vec<u8> {
+    let blocks = ERROR_CORRECTION_BLOCKS[ecc as usize][version as usize] as usize;
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[ecc as usize][version as usize] as usize;
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_len = raw_codewords / blocks;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let block_data = &data[start..start + len];
+        start += len;
+        let mut block = block_data.to_vec();
+        if i < short_blocks {
+            // padding so all blocks line up, skipped when interleaving
+            block.push(0);
+        }
+        block.extend(reed_solomon_remainder(block_data, &divisor));
+        split.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..split[0].len() {
+        for (j, block) in split.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (x, &y) in result.iter_mut().zip(divisor) {
+            *x ^= gf_multiply(y, factor);
+        }
+    }
+    result
+}
+
+/// Multiplies in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_symbol(code: &QrCode, rows: &[&str]) {
+        assert_eq!(code.size(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            let drawn: String = (0..code.size())
+                .map(|x| if code.module(x, y) { '#' } else { '.' })
+                .collect();
+            assert_eq!(drawn, *row, "row {y}");
+        }
+    }
+
+    // symbols from the qrcodegen crate
+    #[test]
+    fn encodes_version_1_medium() {
+        let code = QrCode::encode(b"Flipper", Ecc::Medium, None).unwrap();
+        assert_symbol(
+            &code,
+            &[
+                "#######.##....#######",
+                "#.....#.#.#...#.....#",
+                "#.###.#.#.#.#.#.###.#",
+                "#.###.#..##...#.###.#",
+                "#.###.#.#..##.#.###.#",
+                "#.....#.......#.....#",
+                "#######.#.#.#.#######",
+                ".........#...........",
+                "#..######..#.#..#.###",
+                "..#.##.#..####..##...",
+                "##########.##..#.####",
+                ".#.#....#...###...#.#",
+                "###.###..#.#######..#",
+                "........##..#####..##",
+                "#######.#.##....###..",
+                "#.....#.#.#...######.",
+                "#.###.#.##...##....##",
+                "#.###.#.##..###..##..",
+                "#.###.#..#####..#####",
+                "#.....#....##....####",
+                "#######.###.##.###...",
+            ],
+        );
+    }
+
+    #[test]
+    fn encodes_version_2_low() {
+        let code = QrCode::encode(b"https://example.com", Ecc::Low, None).unwrap();
+        assert_symbol(
+            &code,
+            &[
+                "#######....##.#...#######",
+                "#.....#..##..##.#.#.....#",
+                "#.###.#.....#.#...#.###.#",
+                "#.###.#.#...##..#.#.###.#",
+                "#.###.#.##...#..#.#.###.#",
+                "#.....#....##.#...#.....#",
+                "#######.#.#.#.#.#.#######",
+                "..........##..###........",
+                "##...###.#.#........##...",
+                "#...#..###..######.#####.",
+                "#.##..#.#.#..####..#.#.##",
+                ".##..#...#..##..#.##.#..#",
+                "###..#####.###.##.##....#",
+                "##.....##......##..#...#.",
+                "#..#####..###..#..####.##",
+                "#.###..#...#.....###.##.#",
+                "#...#####.##....#####.#..",
+                "........#...###.#...#....",
+                "#######.#..#....#.#.#...#",
+                "#.....#.###.##.##...#..#.",
+                "#.###.#..##.#.#######.#.#",
+                "#.###.#...#...#.###....##",
+                "#.###.#..#.##..#.....##.#",
+                "#.....#.##.#..####.##...#",
+                "#######.#.#..##.#.#..#..#",
+            ],
+        );
+    }
+
+    #[test]
+    fn rejects_data_over_capacity() {
+        // version 1-H holds 7 bytes
+        assert!(QrCode::encode(&[0; 7], Ecc::High, Some(1)).is_some());
+        assert!(QrCode::encode(&[0; 8], Ecc::High, Some(1)).is_none());
+        assert_eq!(QrCode::encode(&[0; 8], Ecc::High, None).unwrap().size(), 25);
+        // version 40-L holds 2953 bytes
+        assert!(QrCode::encode(&[0; 2953], Ecc::Low, None).is_some());
+        assert!(QrCode::encode(&[0; 2954], Ecc::Low, None).is_none());
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::ffi::CString;
+use core::ffi::{c_char, CStr};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use crate::framebuffer::Framebuffer;
+use crate::label::{builtin_faces, draw_text, Align, Area, Canvas};
+use crate::qr::{Ecc, QrCode, QUIET_ZONE};
+use crate::settings::Settings;
+
+/// Room for a typed URL or text, including the nul.
+pub const INPUT_SIZE: usize = 128;
+
+#[derive(Clone, Copy)]
+pub enum Field {
+    Text,
+    Caption,
+}
+
+/// A QR code typed on the Flipper, with an optional caption below it.
+pub struct QrLabel {
+    pub input: [c_char; INPUT_SIZE],
+    text: CString,
+    caption: CString,
+    pub ecc: Ecc,
+    /// Fixed symbol version, or the smallest that fits when `None`.
+    pub version: Option<u8>,
+}
+
+impl QrLabel {
+    pub fn new() -> Self {
+        Self {
+            input: [0; INPUT_SIZE],
+            text: CString::default(),
+            caption: CString::default(),
+            ecc: Ecc::Medium,
+            version: None,
+        }
+    }
+
+    pub fn text(&self) -> &CStr {
+        &self.text
+    }
+
+    pub fn caption(&self) -> &CStr {
+        &self.caption
+    }
+
+    /// Puts the field in the input to be edited.
+    pub fn edit(&mut self, field: Field) {
+        self.input = [0; INPUT_SIZE];
+        let value = match field {
+            Field::Text => &self.text,
+            Field::Caption => &self.caption,
+        };
+        for (dst, src) in self.input.iter_mut().zip(value.as_bytes()) {
+            *dst = *src as c_char;
+        }
+    }
+
+    pub fn save(&mut self, field: Field) {
+        let input = unsafe { CStr::from_ptr(self.input.as_ptr()) }.into();
+        match field {
+            Field::Text => self.text = input,
+            Field::Caption => self.caption = input,
+        }
+    }
+
+    /// Draws the code as large as it fits with its quiet zone, centered above the caption.
+    pub fn render(&self, settings: &Settings) -> Result<Framebuffer, *const c_char> {
+        let code = QrCode::encode(self.text.as_bytes(), self.ecc, self.version)
+            .ok_or(c_string!("Text too long for version"))?;
+        let mut framebuffer = Framebuffer::new(settings.tag_size);
+        let mut canvas = Canvas::new(&mut framebuffer, settings.rotation);
+        let (width, height) = (canvas.width, canvas.height);
+        let margin = width.min(height) / 20;
+        let caption_height = if self.caption.is_empty() {
+            0
+        } else {
+            height / 5
+        };
+
+        let area = Area {
+            left: margin,
+            top: margin,
+            width: width - 2 * margin,
+            height: height - 2 * margin - caption_height,
+        };
+        draw_qr(&mut canvas, &code, &area)?;
+
+        if caption_height > 0 {
+            let area = Area {
+                left: margin,
+                top: height - margin - caption_height,
+                width: width - 2 * margin,
+                height: caption_height,
+            };
+            let caption = self.caption.to_str().unwrap_or("");
+            draw_text(
+                &mut canvas,
+                &builtin_faces(),
+                &[caption],
+                Align::Center,
+                &area,
+            );
+        }
+
+        if settings.invert {
+            framebuffer.invert();
+        }
+        Ok(framebuffer)
+    }
+}
+
+/// Draws the code with the largest modules that fit with its quiet zone, centered in the area.
+pub fn draw_qr(canvas: &mut Canvas, code: &QrCode, area: &Area) -> Result<(), *const c_char> {
+    let module = area.width.min(area.height) / (code.size() + 2 * QUIET_ZONE);
+    if module == 0 {
+        return Err(c_string!("Code too large for panel"));
+    }
+    let left = area.left + (area.width - code.size() * module) / 2;
+    let top = area.top + (area.height - code.size() * module) / 2;
+    for y in 0..code.size() {
+        for x in 0..code.size() {
+            if code.module(x, y) {
+                let corner = Point::new((left + x * module) as i32, (top + y * module) as i32);
+                let size = Size::new_equal(module as u32);
+                let _ = canvas.fill_solid(&Rectangle::new(corner, size), BinaryColor::On);
+            }
+        }
+    }
+    Ok(())
+}
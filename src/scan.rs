@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use flipperzero::furi::string::FuriString;
+use ufmt::uwrite;
+
+use crate::files;
+use crate::hex;
+use crate::settings::Settings;
+use crate::poller;
+
+/// Room for the typed argument bytes, including the nul.
+pub const INPUT_SIZE: usize = 32;
+
+const CMD: u8 = 0xCD;
+
+/// Opcodes that select the panel, load image data, power the panel or refresh it. Sending them
+/// with arbitrary arguments could leave the tag misconfigured or wear the panel.
+const SKIPPED: [u8; 4] = [0x00, 0x08, 0x09, 0x18];
+
+pub struct Summary {
+    pub sent: usize,
+    pub answered: usize,
+    /// Whether the report could be written to scan.csv.
+    pub saved: bool,
+}
+
+/// Argument bytes typed for an opcode scan, which maps the 0xCD commands a tag revision supports.
+pub struct Scanner {
+    pub input: [c_char; INPUT_SIZE],
+}
+
+fn status(response: Option<&[u8]>) -> &'static str {
+    match response {
+        None => "none",
+        Some([0x00, 0x00]) => "ack",
+        Some([0xFF, 0x00]) => "ready",
+        Some(_) => "other",
+    }
+}
+
+impl Scanner {
+    pub fn new() -> Self {
+        Self {
+            input: [0; INPUT_SIZE],
+        }
+    }
+
+    /// The typed argument bytes sent after each opcode, `None` if they aren't valid hex.
+    pub fn args(&self) -> Option<Vec<u8>> {
+        let text = unsafe { CStr::from_ptr(self.input.as_ptr()) };
+        hex::decode(text.to_str().ok()?)
+    }
+}
+
+/// Sends every opcode not known to be destructive, followed by `args`, to the next tag
+/// presented and writes the response to each to scan.csv. `progress` gets the opcode about to
+/// be sent. Returns `None` if no tag was found.
+pub fn run(settings: &Settings, args: &[u8], progress: &mut dyn FnMut(u8)) -> Option<Summary> {
+    let mut report = FuriString::new();
+    let mut summary = Summary {
+        sent: 0,
+        answered: 0,
+        saved: false,
+    };
+    let found = poller::with_tag(settings, &mut |tag, transport| {
+        let _ = uwrite!(report, "# uid ");
+        hex::encode(tag.uid(), "", &mut report);
+        let _ = uwrite!(report, " args ");
+        hex::encode(args, "", &mut report);
+        let _ = uwrite!(report, "\n# opcode,bits,status,response\n");
+
+        let mut tx = Vec::with_capacity(2 + args.len());
+        let mut rx = [0; 64];
+        for opcode in (0..=0xFF).filter(|opcode| !SKIPPED.contains(opcode)) {
+            progress(opcode);
+            tx.clear();
+            tx.extend_from_slice(&[CMD, opcode]);
+            tx.extend_from_slice(args);
+
+            let bits = transport.tx_rx(&tx, &mut rx, settings.tx_timeout);
+            let response = bits.map(|bits| &rx[..bits.div_ceil(8).min(rx.len())]);
+            summary.sent += 1;
+            if response.is_some() {
+                summary.answered += 1;
+            }
+
+            hex::encode(&[opcode], "", &mut report);
+            let _ = uwrite!(report, ",{},{},", bits.unwrap_or(0), status(response));
+            hex::encode(response.unwrap_or(&[]), "", &mut report);
+            let _ = uwrite!(report, "\n");
+        }
+    });
+    if !found {
+        return None;
+    }
+    summary.saved = files::write("scan.csv", report.as_c_str().to_bytes());
+    Some(summary)
+}
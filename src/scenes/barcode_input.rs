@@ -0,0 +1,45 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::barcode_label::INPUT_SIZE;
+use crate::AppView;
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::BarcodeEntered);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let text_input = app.text_input.as_ptr();
+    app.barcode.edit();
+    sys::text_input_set_header_text(text_input, c_string!("Barcode data"));
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        app.barcode.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::BarcodeEntered) => {
+            app.barcode.save();
+            app.back_to_scene(Scene::BarcodeLabel);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+}
@@ -0,0 +1,96 @@
+use core::ffi::c_void;
+use core::ptr;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::barcode::Symbology;
+use crate::image::Image;
+use crate::AppView;
+
+// item indexes
+const DATA: u32 = 1;
+const WRITE: u32 = 2;
+
+pub unsafe extern "C" fn enter_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    app.set_scene_state(Scene::BarcodeLabel, index);
+    app.send_event(AppEvent::BarcodeItem);
+}
+
+pub unsafe extern "C" fn set_symbology_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    let index = sys::variable_item_get_current_value_index(item);
+    if let Some(symbology) = Symbology::from_index(index) {
+        app.barcode.symbology = symbology;
+        sys::variable_item_set_current_value_text(item, symbology.text().as_ptr());
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let variable_item_list = app.variable_item_list.as_ptr();
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Type"),
+        Symbology::ALL.len() as u8,
+        Some(set_symbology_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.barcode.symbology as u8);
+    set_symbology_callback(item);
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Data"),
+        1,
+        None,
+        ptr::null_mut(),
+    );
+    let data = if app.barcode.data().is_empty() {
+        c_string!("None")
+    } else {
+        app.barcode.data().as_ptr()
+    };
+    sys::variable_item_set_current_value_text(item, data);
+
+    sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Write"),
+        0,
+        None,
+        ptr::null_mut(),
+    );
+
+    sys::variable_item_list_set_enter_callback(variable_item_list, Some(enter_callback), context);
+    sys::variable_item_list_set_selected_item(
+        variable_item_list,
+        app.scene_state(Scene::BarcodeLabel) as u8,
+    );
+    app.switch_to_view(AppView::VariableItemList);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if custom_event(event) != Some(AppEvent::BarcodeItem) {
+        return false;
+    }
+    match app.scene_state(Scene::BarcodeLabel) {
+        DATA => app.next_scene(Scene::BarcodeInput),
+        WRITE => match app.barcode.render(&app.settings) {
+            Ok(framebuffer) => {
+                app.image = Some(Image::from_framebuffer(framebuffer, "barcode"));
+                app.next_scene(Scene::Waiting);
+            }
+            Err(message) => app.show_error(message),
+        },
+        _ => {}
+    }
+    true
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::variable_item_list_reset(app.variable_item_list.as_ptr());
+}
@@ -0,0 +1,79 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, waiting, AppEvent, Scene};
+use crate::AppView;
+
+pub unsafe extern "C" fn dialog_callback(result: sys::DialogExResult, context: *mut c_void) {
+    let app = app(context);
+    match result {
+        sys::DialogExResult_DialogExResultLeft => app.send_event(AppEvent::Skip),
+        sys::DialogExResult_DialogExResultRight => app.send_event(AppEvent::Retry),
+        _ => {}
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let dialog_ex = app.dialog_ex.as_ptr();
+
+    app.text.clear();
+    if let Some(batch) = &app.batch {
+        let _ = uwrite!(app.text, "label {}/{} failed", batch.position(), batch.len());
+    }
+    sys::dialog_ex_set_header(
+        dialog_ex,
+        app.text.as_c_str().as_ptr(),
+        64,
+        0,
+        sys::Align_AlignCenter,
+        sys::Align_AlignTop,
+    );
+    sys::dialog_ex_set_text(
+        dialog_ex,
+        app.error_message,
+        64,
+        32,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+    );
+    sys::dialog_ex_set_left_button_text(dialog_ex, c_string!("Skip"));
+    sys::dialog_ex_set_right_button_text(dialog_ex, c_string!("Retry"));
+    sys::dialog_ex_set_result_callback(dialog_ex, Some(dialog_callback));
+    sys::dialog_ex_set_context(dialog_ex, context);
+
+    app.switch_to_view(AppView::DialogEx);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        // stop the batch here
+        app.next_scene(Scene::Summary);
+        return true;
+    }
+    match custom_event(event) {
+        Some(AppEvent::Skip) => {
+            if let Some(batch) = &mut app.batch {
+                batch.skipped += 1;
+                batch.advance();
+            }
+            app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+            app.back_to_scene(Scene::Waiting);
+            true
+        }
+        Some(AppEvent::Retry) => {
+            app.set_scene_state(Scene::Waiting, waiting::PRESENT);
+            app.back_to_scene(Scene::Waiting);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::dialog_ex_reset(app.dialog_ex.as_ptr());
+}
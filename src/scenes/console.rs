@@ -0,0 +1,70 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::console::INPUT_SIZE;
+use crate::{update_widget, AppView};
+
+/// Scene states, typing a command or waiting for a tag to send it to.
+pub const INPUT: u32 = 0;
+pub const POLL: u32 = 1;
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::ConsoleSend);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    app.set_scene_state(Scene::Console, INPUT);
+    let text_input = app.text_input.as_ptr();
+    sys::text_input_set_header_text(text_input, c_string!("Hex frames, _ between"));
+    // keep the last command so it can be tweaked and sent again
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        app.console.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack
+        && app.scene_state(Scene::Console) == POLL
+    {
+        // stop waiting and go back to the command
+        app.set_scene_state(Scene::Console, INPUT);
+        app.switch_to_view(AppView::TextInput);
+        return true;
+    }
+    match custom_event(event) {
+        Some(AppEvent::ConsoleSend) => {
+            update_widget(app.widget.as_ptr(), c_string!("waiting for tag"));
+            app.switch_to_view(AppView::Widget);
+            app.set_scene_state(Scene::Console, POLL);
+            app.send_event(AppEvent::ConsolePoll);
+            true
+        }
+        Some(AppEvent::ConsolePoll) if app.scene_state(Scene::Console) == POLL => {
+            if app.console.send(&app.settings) {
+                app.next_scene(Scene::ConsoleLog);
+            } else {
+                sys::furi_delay_ms(50);
+                app.send_event(AppEvent::ConsolePoll); // try again until a tag shows up
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+    sys::widget_reset(app.widget.as_ptr());
+}
@@ -0,0 +1,26 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+
+use super::app;
+use crate::AppView;
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let text_box = app.text_box.as_ptr();
+
+    app.console.log(&mut app.text);
+    sys::text_box_set_font(text_box, sys::TextBoxFont_TextBoxFontText);
+    sys::text_box_set_text(text_box, app.text.as_c_str().as_ptr());
+    // newest command is at the bottom
+    sys::text_box_set_focus(text_box, sys::TextBoxFocus_TextBoxFocusEnd);
+    app.switch_to_view(AppView::TextBox);
+}
+
+pub unsafe extern "C" fn on_event(_context: *mut c_void, _event: sys::SceneManagerEvent) -> bool {
+    false // back returns to the command entry
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_box_reset(app.text_box.as_ptr());
+}
@@ -0,0 +1,36 @@
+use core::ffi::{c_void, CStr};
+use flipperzero::dialogs::{DialogFileBrowserOptions, DialogsApp};
+use flipperzero::println;
+use flipperzero_sys as sys;
+
+use super::{app, custom_event, AppEvent, Scene};
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let mut dialogs_app = DialogsApp::open();
+    let file_browser_options = DialogFileBrowserOptions::new()
+        .set_hide_dot_files(true)
+        .set_extension(CStr::from_bytes_until_nul(b"csv\0").unwrap())
+        .set_hide_ext(false);
+    let Some(file_path) = dialogs_app.show_file_browser(None, Some(&file_browser_options)) else {
+        println!("no file selected");
+        app.back_to_scene(Scene::MainMenu);
+        return;
+    };
+    println!("csv selected {}", file_path);
+    app.csv_path = Some(file_path);
+    app.send_event(AppEvent::CsvSelected);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::CsvSelected) => {
+            app.next_scene(Scene::TemplatePick);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(_context: *mut c_void) {}
@@ -0,0 +1,36 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::app;
+use crate::emulate::Emulator;
+use crate::{update_widget, AppView};
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let widget = app.widget.as_ptr();
+    update_widget(widget, c_string!("emulating tag"));
+    sys::widget_add_string_element(
+        widget,
+        64,
+        50,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontSecondary,
+        c_string!("saved when leaving"),
+    );
+    app.emulator = Some(Emulator::start(widget));
+    app.switch_to_view(AppView::Widget);
+}
+
+pub unsafe extern "C" fn on_event(_context: *mut c_void, _event: sys::SceneManagerEvent) -> bool {
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    if let Some(emulator) = app.emulator.take() {
+        emulator.stop();
+    }
+    sys::widget_reset(app.widget.as_ptr());
+}
@@ -0,0 +1,28 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+
+use super::{app, Scene};
+use crate::{update_widget, AppView};
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    update_widget(app.widget.as_ptr(), app.error_message);
+    app.switch_to_view(AppView::Widget);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        // keep sweeping after a failed write
+        if app.mappings.is_some() && app.back_to_scene(Scene::Waiting) {
+            return true;
+        }
+        return app.back_to_scene(Scene::MainMenu);
+    }
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
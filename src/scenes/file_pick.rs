@@ -0,0 +1,62 @@
+use core::ffi::{c_void, CStr};
+use flipperzero::dialogs::{DialogFileBrowserOptions, DialogsApp};
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::batch::Batch;
+use crate::image;
+
+/// Scene states, picking a single image or the folder of images for a batch.
+pub const SINGLE: u32 = 0;
+pub const BATCH: u32 = 1;
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let mut dialogs_app = DialogsApp::open();
+    let file_browser_options = DialogFileBrowserOptions::new()
+        .set_hide_dot_files(true)
+        .set_extension(CStr::from_bytes_until_nul(b"*\0").unwrap())
+        .set_hide_ext(false);
+    let Some(file_path) = dialogs_app.show_file_browser(None, Some(&file_browser_options)) else {
+        println!("no file selected");
+        app.back_to_scene(Scene::MainMenu);
+        return;
+    };
+    println!("file selected {}", file_path);
+    if !image::is_image(file_path.as_c_str()) {
+        app.show_error(c_string!("Not a PBM or PGM file"));
+        return;
+    }
+
+    if app.scene_state(Scene::FilePick) == BATCH {
+        // any image picks its whole folder
+        app.batch = Batch::from_folder(file_path.as_c_str());
+        if app.batch.is_none() {
+            app.show_error(c_string!("No images in folder"));
+            return;
+        }
+    } else {
+        app.settings.file_path = Some(file_path);
+        app.settings.save();
+    }
+    app.send_event(AppEvent::FileSelected);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::FileSelected) if app.batch.is_some() => {
+            app.next_scene(Scene::Waiting);
+            true
+        }
+        Some(AppEvent::FileSelected) => {
+            app.next_scene(Scene::Preview);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(_context: *mut c_void) {}
@@ -0,0 +1,63 @@
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::history::History;
+use crate::AppView;
+
+pub unsafe extern "C" fn submenu_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    app.set_scene_state(Scene::History, index);
+    app.send_event(AppEvent::ShowEntry);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let submenu = app.submenu.as_ptr();
+
+    if app.history.is_none() {
+        app.history = History::load();
+    }
+    let Some(history) = &app.history else {
+        app.show_error(c_string!("No writes logged yet"));
+        return;
+    };
+
+    sys::submenu_set_header(submenu, c_string!("History"));
+    let mut label = FuriString::new();
+    for index in 0..history.len() {
+        let Some(fields) = history.get(index) else { continue };
+        label.clear();
+        // month-day hour:minute, result and file name
+        let time = fields.time.get(5..16).unwrap_or(fields.time);
+        let _ = uwrite!(label, "{} {} {}", time, fields.result, fields.file_name());
+        sys::submenu_add_item(
+            submenu,
+            label.as_c_str().as_ptr(),
+            index as u32,
+            Some(submenu_callback),
+            context,
+        );
+    }
+    sys::submenu_set_selected_item(submenu, app.scene_state(Scene::History));
+    app.switch_to_view(AppView::Submenu);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::ShowEntry) => {
+            app.next_scene(Scene::HistoryEntry);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::submenu_reset(app.submenu.as_ptr());
+}
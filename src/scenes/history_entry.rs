@@ -0,0 +1,44 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use ufmt::uwrite;
+
+use super::{app, Scene};
+use crate::AppView;
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let text_box = app.text_box.as_ptr();
+
+    app.text.clear();
+    let index = app.scene_state(Scene::History) as usize;
+    if let Some(fields) = app.history.as_ref().and_then(|history| history.get(index)) {
+        let _ = uwrite!(
+            app.text,
+            "{}\nUID {}\nATQA {} SAK {}\nPanel {}\"\n{}\nHash {}\nChunks {} Retries {}\n{} ms\nResult {}",
+            fields.time,
+            fields.uid,
+            fields.atqa,
+            fields.sak,
+            fields.panel,
+            fields.file_path,
+            fields.hash,
+            fields.chunks,
+            fields.retries,
+            fields.elapsed_ms,
+            fields.result
+        );
+    }
+
+    sys::text_box_set_font(text_box, sys::TextBoxFont_TextBoxFontText);
+    sys::text_box_set_text(text_box, app.text.as_c_str().as_ptr());
+    app.switch_to_view(AppView::TextBox);
+}
+
+pub unsafe extern "C" fn on_event(_context: *mut c_void, _event: sys::SceneManagerEvent) -> bool {
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_box_reset(app.text_box.as_ptr());
+}
@@ -0,0 +1,88 @@
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent};
+use crate::driver;
+use crate::history::History;
+use crate::hex;
+use crate::poller;
+use crate::transport::TagInfo;
+use crate::{update_widget, App, AppView};
+
+/// Describes a tag, inferring the panel from the last time it was written.
+fn describe(app: &mut App, tag: &TagInfo) {
+    let uid = tag.uid();
+    let mut uid_hex = FuriString::new();
+    hex::encode(uid, "", &mut uid_hex);
+
+    let text = &mut app.text;
+    text.clear();
+    let _ = uwrite!(text, "UID ");
+    hex::encode(uid, " ", text);
+    let _ = uwrite!(text, "\nATQA ");
+    hex::encode(&tag.atqa, " ", text);
+    let _ = uwrite!(text, " SAK ");
+    hex::encode(&[tag.sak], "", text);
+
+    let Some(driver) = driver::find(tag) else {
+        let _ = uwrite!(text, "\nNot an e-paper tag");
+        return;
+    };
+
+    if app.history.is_none() {
+        app.history = History::load();
+    }
+    let uid_hex = uid_hex.as_c_str().to_str().unwrap_or("");
+    match app.history.as_ref().and_then(|history| history.find_uid(uid_hex)) {
+        Some(fields) => {
+            let _ = uwrite!(
+                text,
+                "\n{} {}\" tag\nLast write {} {}\n{}",
+                driver.name(),
+                fields.panel,
+                fields.time,
+                fields.result,
+                fields.file_path
+            );
+        }
+        None => {
+            let _ = uwrite!(text, "\n{} tag, size unknown\nNo writes logged", driver.name());
+        }
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    update_widget(app.widget.as_ptr(), c_string!("waiting for tag"));
+    app.switch_to_view(AppView::Widget);
+    app.send_event(AppEvent::WaitForTag);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::WaitForTag) => {
+            // read only, the tag is never sent a command
+            let Some(tag) = poller::detect_any(&app.settings) else {
+                sys::furi_delay_ms(50);
+                app.send_event(AppEvent::WaitForTag);
+                return true;
+            };
+            describe(app, &tag);
+            sys::text_box_set_font(app.text_box.as_ptr(), sys::TextBoxFont_TextBoxFontText);
+            sys::text_box_set_text(app.text_box.as_ptr(), app.text.as_c_str().as_ptr());
+            app.switch_to_view(AppView::TextBox);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+    sys::text_box_reset(app.text_box.as_ptr());
+}
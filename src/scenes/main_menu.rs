@@ -0,0 +1,94 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, file_pick, AppEvent, Scene};
+use crate::mapping::Mappings;
+use crate::AppView;
+
+pub unsafe extern "C" fn submenu_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    sys::view_dispatcher_send_custom_event(app.view_dispatcher.as_ptr(), index);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let submenu = app.submenu.as_ptr();
+    app.batch = None;
+    app.csv_path = None;
+    app.mappings = None;
+    app.history = None; // reload, there may be new writes
+
+    // item index is the event sent when the item is selected
+    for (label, event) in [
+        (c_string!("Open Image"), AppEvent::OpenImage),
+        (c_string!("Write Tag"), AppEvent::WriteTag),
+        (c_string!("Text Label"), AppEvent::TextLabel),
+        (c_string!("QR Code"), AppEvent::QrCode),
+        (c_string!("Barcode"), AppEvent::Barcode),
+        (c_string!("Templates"), AppEvent::Templates),
+        (c_string!("Mail Merge"), AppEvent::Merge),
+        (c_string!("Batch Write"), AppEvent::BatchWrite),
+        (c_string!("Sweep Mapped Tags"), AppEvent::Sweep),
+        (c_string!("Identify Tag"), AppEvent::Identify),
+        (c_string!("Raw Console"), AppEvent::Console),
+        (c_string!("Opcode Scan"), AppEvent::Scan),
+        (c_string!("Emulate Tag"), AppEvent::Emulate),
+        (c_string!("History"), AppEvent::History),
+        (c_string!("Settings"), AppEvent::Settings),
+    ] {
+        sys::submenu_add_item(submenu, label, event.into(), Some(submenu_callback), context);
+    }
+
+    sys::submenu_set_selected_item(submenu, app.scene_state(Scene::MainMenu));
+    app.switch_to_view(AppView::Submenu);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    let Some(event) = custom_event(event) else {
+        return false;
+    };
+    app.set_scene_state(Scene::MainMenu, event.into());
+    match event {
+        AppEvent::OpenImage => {
+            app.set_scene_state(Scene::FilePick, file_pick::SINGLE);
+            app.next_scene(Scene::FilePick)
+        }
+        AppEvent::BatchWrite => {
+            app.set_scene_state(Scene::FilePick, file_pick::BATCH);
+            app.next_scene(Scene::FilePick)
+        }
+        AppEvent::WriteTag if app.settings.file_path.is_some() => app.next_scene(Scene::Preview),
+        AppEvent::WriteTag => {
+            app.set_scene_state(Scene::FilePick, file_pick::SINGLE);
+            app.next_scene(Scene::FilePick)
+        }
+        AppEvent::TextLabel => app.next_scene(Scene::TextLabel),
+        AppEvent::QrCode => app.next_scene(Scene::QrLabel),
+        AppEvent::Barcode => app.next_scene(Scene::BarcodeLabel),
+        AppEvent::Templates => app.next_scene(Scene::TemplatePick),
+        AppEvent::Merge => app.next_scene(Scene::CsvPick),
+        AppEvent::Sweep => {
+            app.mappings = Mappings::load();
+            if app.mappings.is_some() {
+                app.next_scene(Scene::Waiting)
+            } else {
+                app.show_error(c_string!("No tags in mapping.csv"))
+            }
+        }
+        AppEvent::Identify => app.next_scene(Scene::Identify),
+        AppEvent::Console => app.next_scene(Scene::Console),
+        AppEvent::Scan => app.next_scene(Scene::Scan),
+        AppEvent::Emulate => app.next_scene(Scene::Emulate),
+        AppEvent::History => app.next_scene(Scene::History),
+        AppEvent::Settings => app.next_scene(Scene::Settings),
+        _ => return false,
+    }
+    true
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::submenu_reset(app.submenu.as_ptr());
+}
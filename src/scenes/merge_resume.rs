@@ -0,0 +1,76 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::AppView;
+
+pub unsafe extern "C" fn dialog_callback(result: sys::DialogExResult, context: *mut c_void) {
+    let app = app(context);
+    match result {
+        sys::DialogExResult_DialogExResultLeft => app.send_event(AppEvent::Restart),
+        sys::DialogExResult_DialogExResultRight => app.send_event(AppEvent::Resume),
+        _ => {}
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let dialog_ex = app.dialog_ex.as_ptr();
+
+    app.text.clear();
+    if let Some(merge) = app.batch.as_ref().and_then(|batch| batch.merge()) {
+        let _ = uwrite!(
+            app.text,
+            "{} of {} rows written",
+            merge.written(),
+            merge.len()
+        );
+    }
+    sys::dialog_ex_set_header(
+        dialog_ex,
+        c_string!("Merge started before"),
+        64,
+        0,
+        sys::Align_AlignCenter,
+        sys::Align_AlignTop,
+    );
+    sys::dialog_ex_set_text(
+        dialog_ex,
+        app.text.as_c_str().as_ptr(),
+        64,
+        32,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+    );
+    sys::dialog_ex_set_left_button_text(dialog_ex, c_string!("Restart"));
+    sys::dialog_ex_set_right_button_text(dialog_ex, c_string!("Continue"));
+    sys::dialog_ex_set_result_callback(dialog_ex, Some(dialog_callback));
+    sys::dialog_ex_set_context(dialog_ex, context);
+
+    app.switch_to_view(AppView::DialogEx);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::Restart) => {
+            if let Some(batch) = &mut app.batch {
+                batch.restart();
+            }
+            app.next_scene(Scene::Waiting);
+            true
+        }
+        Some(AppEvent::Resume) => {
+            app.next_scene(Scene::Waiting);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::dialog_ex_reset(app.dialog_ex.as_ptr());
+}
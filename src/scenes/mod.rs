@@ -0,0 +1,339 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+
+mod barcode_input;
+mod barcode_label;
+mod batch_failed;
+mod console;
+mod console_log;
+mod csv_pick;
+mod emulate;
+mod error;
+mod file_pick;
+mod history;
+mod history_entry;
+mod identify;
+mod main_menu;
+mod merge_resume;
+mod preview;
+mod qr_input;
+mod qr_label;
+mod result;
+mod scan;
+mod settings;
+mod summary;
+mod template_field;
+mod template_pick;
+mod text_label;
+mod text_line;
+mod up_to_date;
+mod waiting;
+mod writing;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Scene {
+    // must match order of handlers below
+    MainMenu = 0,
+    FilePick = 1,
+    Preview = 2,
+    Settings = 3,
+    Waiting = 4,
+    Writing = 5,
+    Result = 6,
+    Error = 7,
+    BatchFailed = 8,
+    Summary = 9,
+    History = 10,
+    HistoryEntry = 11,
+    UpToDate = 12,
+    Identify = 13,
+    Console = 14,
+    ConsoleLog = 15,
+    Scan = 16,
+    Emulate = 17,
+    TextLabel = 18,
+    TextLine = 19,
+    QrLabel = 20,
+    QrInput = 21,
+    BarcodeLabel = 22,
+    BarcodeInput = 23,
+    TemplatePick = 24,
+    TemplateField = 25,
+    CsvPick = 26,
+    MergeResume = 27,
+}
+
+impl Scene {
+    pub const COUNT: usize = 28;
+
+    /// Scenes an image to write comes from, returned to once it's written or skipped.
+    pub const SOURCES: [Scene; 5] = [
+        Self::Preview,
+        Self::TextLabel,
+        Self::QrLabel,
+        Self::BarcodeLabel,
+        Self::TemplatePick,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent {
+    OpenImage,
+    WriteTag,
+    Settings,
+    FileSelected,
+    WaitForTag,
+    TagFound,
+    StartWrite,
+    BatchWrite,
+    Retry,
+    Skip,
+    Sweep,
+    History,
+    ShowEntry,
+    Force,
+    Identify,
+    Console,
+    ConsoleSend,
+    Scan,
+    StartScan,
+    Emulate,
+    TextLabel,
+    LabelItem,
+    LineEntered,
+    QrCode,
+    QrItem,
+    QrEntered,
+    Barcode,
+    BarcodeItem,
+    BarcodeEntered,
+    Templates,
+    TemplatePicked,
+    FieldEntered,
+    Merge,
+    CsvSelected,
+    Restart,
+    Resume,
+    ConsolePoll,
+    ScanPoll,
+}
+
+impl From<AppEvent> for u32 {
+    fn from(event: AppEvent) -> u32 {
+        match event {
+            AppEvent::OpenImage => 1,
+            AppEvent::WriteTag => 2,
+            AppEvent::Settings => 3,
+            AppEvent::FileSelected => 4,
+            AppEvent::WaitForTag => 5,
+            AppEvent::TagFound => 6,
+            AppEvent::StartWrite => 7,
+            AppEvent::BatchWrite => 8,
+            AppEvent::Retry => 9,
+            AppEvent::Skip => 10,
+            AppEvent::Sweep => 11,
+            AppEvent::History => 12,
+            AppEvent::ShowEntry => 13,
+            AppEvent::Force => 14,
+            AppEvent::Identify => 15,
+            AppEvent::Console => 16,
+            AppEvent::ConsoleSend => 17,
+            AppEvent::Scan => 18,
+            AppEvent::StartScan => 19,
+            AppEvent::Emulate => 20,
+            AppEvent::TextLabel => 21,
+            AppEvent::LabelItem => 22,
+            AppEvent::LineEntered => 23,
+            AppEvent::QrCode => 24,
+            AppEvent::QrItem => 25,
+            AppEvent::QrEntered => 26,
+            AppEvent::Barcode => 27,
+            AppEvent::BarcodeItem => 28,
+            AppEvent::BarcodeEntered => 29,
+            AppEvent::Templates => 30,
+            AppEvent::TemplatePicked => 31,
+            AppEvent::FieldEntered => 32,
+            AppEvent::Merge => 33,
+            AppEvent::CsvSelected => 34,
+            AppEvent::Restart => 35,
+            AppEvent::Resume => 36,
+            AppEvent::ConsolePoll => 37,
+            AppEvent::ScanPoll => 38,
+        }
+    }
+}
+
+impl TryFrom<u32> for AppEvent {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(AppEvent::OpenImage),
+            2 => Ok(AppEvent::WriteTag),
+            3 => Ok(AppEvent::Settings),
+            4 => Ok(AppEvent::FileSelected),
+            5 => Ok(AppEvent::WaitForTag),
+            6 => Ok(AppEvent::TagFound),
+            7 => Ok(AppEvent::StartWrite),
+            8 => Ok(AppEvent::BatchWrite),
+            9 => Ok(AppEvent::Retry),
+            10 => Ok(AppEvent::Skip),
+            11 => Ok(AppEvent::Sweep),
+            12 => Ok(AppEvent::History),
+            13 => Ok(AppEvent::ShowEntry),
+            14 => Ok(AppEvent::Force),
+            15 => Ok(AppEvent::Identify),
+            16 => Ok(AppEvent::Console),
+            17 => Ok(AppEvent::ConsoleSend),
+            18 => Ok(AppEvent::Scan),
+            19 => Ok(AppEvent::StartScan),
+            20 => Ok(AppEvent::Emulate),
+            21 => Ok(AppEvent::TextLabel),
+            22 => Ok(AppEvent::LabelItem),
+            23 => Ok(AppEvent::LineEntered),
+            24 => Ok(AppEvent::QrCode),
+            25 => Ok(AppEvent::QrItem),
+            26 => Ok(AppEvent::QrEntered),
+            27 => Ok(AppEvent::Barcode),
+            28 => Ok(AppEvent::BarcodeItem),
+            29 => Ok(AppEvent::BarcodeEntered),
+            30 => Ok(AppEvent::Templates),
+            31 => Ok(AppEvent::TemplatePicked),
+            32 => Ok(AppEvent::FieldEntered),
+            33 => Ok(AppEvent::Merge),
+            34 => Ok(AppEvent::CsvSelected),
+            35 => Ok(AppEvent::Restart),
+            36 => Ok(AppEvent::Resume),
+            37 => Ok(AppEvent::ConsolePoll),
+            38 => Ok(AppEvent::ScanPoll),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Returns the app event carried by a scene manager event, if it is a custom event we know.
+pub fn custom_event(event: sys::SceneManagerEvent) -> Option<AppEvent> {
+    if event.type_ != sys::SceneManagerEventType_SceneManagerEventTypeCustom {
+        return None;
+    }
+    match AppEvent::try_from(event.event) {
+        Ok(event) => Some(event),
+        Err(value) => {
+            flipperzero::println!("unknown app event {}", value);
+            None
+        }
+    }
+}
+
+/// The scene manager only uses the handler table from the main thread.
+pub struct Handlers(sys::SceneManagerHandlers);
+unsafe impl Sync for Handlers {}
+
+static ON_ENTER: [sys::AppSceneOnEnterCallback; Scene::COUNT] = [
+    Some(main_menu::on_enter),
+    Some(file_pick::on_enter),
+    Some(preview::on_enter),
+    Some(settings::on_enter),
+    Some(waiting::on_enter),
+    Some(writing::on_enter),
+    Some(result::on_enter),
+    Some(error::on_enter),
+    Some(batch_failed::on_enter),
+    Some(summary::on_enter),
+    Some(history::on_enter),
+    Some(history_entry::on_enter),
+    Some(up_to_date::on_enter),
+    Some(identify::on_enter),
+    Some(console::on_enter),
+    Some(console_log::on_enter),
+    Some(scan::on_enter),
+    Some(emulate::on_enter),
+    Some(text_label::on_enter),
+    Some(text_line::on_enter),
+    Some(qr_label::on_enter),
+    Some(qr_input::on_enter),
+    Some(barcode_label::on_enter),
+    Some(barcode_input::on_enter),
+    Some(template_pick::on_enter),
+    Some(template_field::on_enter),
+    Some(csv_pick::on_enter),
+    Some(merge_resume::on_enter),
+];
+
+static ON_EVENT: [sys::AppSceneOnEventCallback; Scene::COUNT] = [
+    Some(main_menu::on_event),
+    Some(file_pick::on_event),
+    Some(preview::on_event),
+    Some(settings::on_event),
+    Some(waiting::on_event),
+    Some(writing::on_event),
+    Some(result::on_event),
+    Some(error::on_event),
+    Some(batch_failed::on_event),
+    Some(summary::on_event),
+    Some(history::on_event),
+    Some(history_entry::on_event),
+    Some(up_to_date::on_event),
+    Some(identify::on_event),
+    Some(console::on_event),
+    Some(console_log::on_event),
+    Some(scan::on_event),
+    Some(emulate::on_event),
+    Some(text_label::on_event),
+    Some(text_line::on_event),
+    Some(qr_label::on_event),
+    Some(qr_input::on_event),
+    Some(barcode_label::on_event),
+    Some(barcode_input::on_event),
+    Some(template_pick::on_event),
+    Some(template_field::on_event),
+    Some(csv_pick::on_event),
+    Some(merge_resume::on_event),
+];
+
+static ON_EXIT: [sys::AppSceneOnExitCallback; Scene::COUNT] = [
+    Some(main_menu::on_exit),
+    Some(file_pick::on_exit),
+    Some(preview::on_exit),
+    Some(settings::on_exit),
+    Some(waiting::on_exit),
+    Some(writing::on_exit),
+    Some(result::on_exit),
+    Some(error::on_exit),
+    Some(batch_failed::on_exit),
+    Some(summary::on_exit),
+    Some(history::on_exit),
+    Some(history_entry::on_exit),
+    Some(up_to_date::on_exit),
+    Some(identify::on_exit),
+    Some(console::on_exit),
+    Some(console_log::on_exit),
+    Some(scan::on_exit),
+    Some(emulate::on_exit),
+    Some(text_label::on_exit),
+    Some(text_line::on_exit),
+    Some(qr_label::on_exit),
+    Some(qr_input::on_exit),
+    Some(barcode_label::on_exit),
+    Some(barcode_input::on_exit),
+    Some(template_pick::on_exit),
+    Some(template_field::on_exit),
+    Some(csv_pick::on_exit),
+    Some(merge_resume::on_exit),
+];
+
+static HANDLERS: Handlers = Handlers(sys::SceneManagerHandlers {
+    on_enter_handlers: ON_ENTER.as_ptr(),
+    on_event_handlers: ON_EVENT.as_ptr(),
+    on_exit_handlers: ON_EXIT.as_ptr(),
+    scene_num: Scene::COUNT as u32,
+});
+
+pub fn handlers() -> *const sys::SceneManagerHandlers {
+    &HANDLERS.0
+}
+
+/// Recovers the app from the context pointer passed to every scene callback.
+unsafe fn app<'a>(context: *mut c_void) -> &'a mut crate::App {
+    &mut *(context as *mut crate::App)
+}
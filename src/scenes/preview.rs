@@ -0,0 +1,97 @@
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::image;
+use crate::AppView;
+
+pub unsafe extern "C" fn button_callback(
+    result: sys::GuiButtonType,
+    type_: sys::InputType,
+    context: *mut c_void,
+) {
+    let app = app(context);
+    if type_ == sys::InputType_InputTypeShort && result == sys::GuiButtonType_GuiButtonTypeRight {
+        app.send_event(AppEvent::WriteTag);
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let Some(file_path) = &app.settings.file_path else {
+        app.show_error(c_string!("No file selected"));
+        return;
+    };
+
+    match image::load(file_path.as_c_str(), &app.settings) {
+        Ok(image) => app.image = Some(image),
+        Err(error) => {
+            app.show_error(error.message());
+            return;
+        }
+    }
+
+    let widget = app.widget.as_ptr();
+    let mut text = FuriString::new();
+    let _ = uwrite!(
+        text,
+        "{}x{} image",
+        app.settings.tag_size.width(),
+        app.settings.tag_size.height()
+    );
+    sys::widget_reset(widget);
+    sys::widget_add_string_element(
+        widget,
+        64,
+        12,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontSecondary,
+        file_path.as_c_str().as_ptr(),
+    );
+    sys::widget_add_string_element(
+        widget,
+        64,
+        28,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontPrimary,
+        text.as_c_str().as_ptr(),
+    );
+    sys::widget_add_string_element(
+        widget,
+        64,
+        40,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontSecondary,
+        app.settings.tag_size.text().as_ptr(),
+    );
+    sys::widget_add_button_element(
+        widget,
+        sys::GuiButtonType_GuiButtonTypeRight,
+        c_string!("Write"),
+        Some(button_callback),
+        context,
+    );
+    app.switch_to_view(AppView::Widget);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::WriteTag) => {
+            app.next_scene(Scene::Waiting);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
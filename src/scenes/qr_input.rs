@@ -0,0 +1,52 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::qr_label::field;
+use super::{app, custom_event, AppEvent, Scene};
+use crate::qr_label::{Field, INPUT_SIZE};
+use crate::AppView;
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::QrEntered);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let text_input = app.text_input.as_ptr();
+    let field = field(app.scene_state(Scene::QrInput));
+    app.qr.edit(field);
+    let header = match field {
+        Field::Text => c_string!("URL or text"),
+        Field::Caption => c_string!("Caption, may be empty"),
+    };
+    sys::text_input_set_header_text(text_input, header);
+    sys::text_input_set_minimum_length(text_input, 0);
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        app.qr.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::QrEntered) => {
+            app.qr.save(field(app.scene_state(Scene::QrInput)));
+            app.back_to_scene(Scene::QrLabel);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+}
@@ -0,0 +1,141 @@
+use core::ffi::{c_char, c_void, CStr};
+use core::ptr;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::image::Image;
+use crate::qr::{Ecc, MAX_VERSION};
+use crate::qr_label::Field;
+use crate::AppView;
+
+// item indexes
+const TEXT: u32 = 0;
+const CAPTION: u32 = 1;
+const WRITE: u32 = 4;
+
+pub unsafe extern "C" fn enter_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    app.set_scene_state(Scene::QrLabel, index);
+    app.send_event(AppEvent::QrItem);
+}
+
+pub unsafe extern "C" fn set_ecc_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    if let Some(ecc) = Ecc::from_index(sys::variable_item_get_current_value_index(item)) {
+        app.qr.ecc = ecc;
+        sys::variable_item_set_current_value_text(item, ecc.text().as_ptr());
+    }
+}
+
+pub unsafe extern "C" fn set_version_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    let index = sys::variable_item_get_current_value_index(item);
+    app.qr.version = (index > 0).then_some(index);
+    let mut text = FuriString::new();
+    match app.qr.version {
+        Some(version) => {
+            let _ = uwrite!(text, "{}", version);
+        }
+        None => {
+            let _ = uwrite!(text, "Auto");
+        }
+    }
+    sys::variable_item_set_current_value_text(item, text.as_c_str().as_ptr());
+}
+
+unsafe fn add_text_item(
+    variable_item_list: *mut sys::VariableItemList,
+    label: *const c_char,
+    value: &CStr,
+) {
+    let item = sys::variable_item_list_add(variable_item_list, label, 1, None, ptr::null_mut());
+    let value = if value.is_empty() {
+        c_string!("None")
+    } else {
+        value.as_ptr()
+    };
+    sys::variable_item_set_current_value_text(item, value);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let variable_item_list = app.variable_item_list.as_ptr();
+
+    add_text_item(variable_item_list, c_string!("Text"), app.qr.text());
+    add_text_item(variable_item_list, c_string!("Caption"), app.qr.caption());
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Level"),
+        Ecc::ALL.len() as u8,
+        Some(set_ecc_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.qr.ecc as u8);
+    set_ecc_callback(item);
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Version"),
+        MAX_VERSION + 1,
+        Some(set_version_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.qr.version.unwrap_or(0));
+    set_version_callback(item);
+
+    sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Write"),
+        0,
+        None,
+        ptr::null_mut(),
+    );
+
+    sys::variable_item_list_set_enter_callback(variable_item_list, Some(enter_callback), context);
+    sys::variable_item_list_set_selected_item(
+        variable_item_list,
+        app.scene_state(Scene::QrLabel) as u8,
+    );
+    app.switch_to_view(AppView::VariableItemList);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if custom_event(event) != Some(AppEvent::QrItem) {
+        return false;
+    }
+    match app.scene_state(Scene::QrLabel) {
+        TEXT | CAPTION => {
+            app.set_scene_state(Scene::QrInput, app.scene_state(Scene::QrLabel));
+            app.next_scene(Scene::QrInput);
+        }
+        WRITE if app.qr.text().is_empty() => app.show_error(c_string!("No text to encode")),
+        WRITE => match app.qr.render(&app.settings) {
+            Ok(framebuffer) => {
+                app.image = Some(Image::from_framebuffer(framebuffer, "QR code"));
+                app.next_scene(Scene::Waiting);
+            }
+            Err(message) => app.show_error(message),
+        },
+        _ => {}
+    }
+    true
+}
+
+/// The field a scene state of the input scene stands for.
+pub fn field(state: u32) -> Field {
+    if state == CAPTION {
+        Field::Caption
+    } else {
+        Field::Text
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::variable_item_list_reset(app.variable_item_list.as_ptr());
+}
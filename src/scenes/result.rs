@@ -0,0 +1,26 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::app;
+use crate::{update_widget, AppView};
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    update_widget(app.widget.as_ptr(), c_string!("done!"));
+    app.switch_to_view(AppView::Widget);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        // preview reloads the file so the same image can be written again, labels are redrawn
+        return app.back_to_source();
+    }
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
@@ -0,0 +1,103 @@
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::hex;
+use crate::scan::{self, INPUT_SIZE};
+use crate::{update_widget, AppView};
+
+/// Scene states, typing the arguments or waiting for a tag to scan.
+pub const INPUT: u32 = 0;
+pub const POLL: u32 = 1;
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::StartScan);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    app.set_scene_state(Scene::Scan, INPUT);
+    let text_input = app.text_input.as_ptr();
+    sys::text_input_set_header_text(text_input, c_string!("Args after opcode (hex)"));
+    sys::text_input_set_minimum_length(text_input, 0); // no arguments is a valid scan
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        app.scanner.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack
+        && app.scene_state(Scene::Scan) == POLL
+    {
+        // stop waiting and go back to the arguments
+        app.set_scene_state(Scene::Scan, INPUT);
+        app.switch_to_view(AppView::TextInput);
+        return true;
+    }
+    match custom_event(event) {
+        Some(AppEvent::StartScan) => {
+            if app.scanner.args().is_none() {
+                app.show_error(c_string!("Args aren't hex"));
+                return true;
+            }
+            update_widget(app.widget.as_ptr(), c_string!("waiting for tag"));
+            app.switch_to_view(AppView::Widget);
+            app.set_scene_state(Scene::Scan, POLL);
+            app.send_event(AppEvent::ScanPoll);
+            true
+        }
+        Some(AppEvent::ScanPoll) if app.scene_state(Scene::Scan) == POLL => {
+            let args = app.scanner.args().unwrap_or_default();
+            let widget = app.widget.as_ptr();
+            let mut label = FuriString::new();
+            let summary = scan::run(&app.settings, &args, &mut |opcode| {
+                label.clear();
+                let _ = uwrite!(label, "opcode CD ");
+                hex::encode(&[opcode], "", &mut label);
+                update_widget(widget, label.as_c_str().as_ptr());
+            });
+
+            let Some(summary) = summary else {
+                sys::furi_delay_ms(50);
+                app.send_event(AppEvent::ScanPoll); // try again until a tag shows up
+                return true;
+            };
+            app.set_scene_state(Scene::Scan, INPUT);
+            if !summary.saved {
+                app.show_error(c_string!("Can't save scan.csv"));
+                return true;
+            }
+            app.text.clear();
+            let _ = uwrite!(app.text, "{}/{} answered", summary.answered, summary.sent);
+            update_widget(widget, app.text.as_c_str().as_ptr());
+            sys::widget_add_string_element(
+                widget,
+                64,
+                50,
+                sys::Align_AlignCenter,
+                sys::Align_AlignCenter,
+                sys::Font_FontSecondary,
+                c_string!("saved to scan.csv"),
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+    sys::widget_reset(app.widget.as_ptr());
+}
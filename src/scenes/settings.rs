@@ -0,0 +1,177 @@
+use core::ffi::{c_char, c_void};
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::app;
+use crate::framebuffer::Rotation;
+use crate::tag::TagSize;
+use crate::AppView;
+
+const MAX_RETRIES: u8 = 5;
+
+fn on_off(value: bool) -> *const c_char {
+    if value {
+        c_string!("On")
+    } else {
+        c_string!("Off")
+    }
+}
+
+pub unsafe extern "C" fn set_tag_size_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    if let Some(tag_size) = TagSize::from_index(sys::variable_item_get_current_value_index(item)) {
+        app.settings.tag_size = tag_size;
+        sys::variable_item_set_current_value_text(item, tag_size.text().as_ptr());
+    }
+}
+
+pub unsafe extern "C" fn set_rotation_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    if let Some(rotation) = Rotation::from_index(sys::variable_item_get_current_value_index(item)) {
+        app.settings.rotation = rotation;
+        sys::variable_item_set_current_value_text(item, rotation.text().as_ptr());
+    }
+}
+
+pub unsafe extern "C" fn set_dithering_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.dithering = sys::variable_item_get_current_value_index(item) == 1;
+    sys::variable_item_set_current_value_text(item, on_off(app.settings.dithering));
+}
+
+pub unsafe extern "C" fn set_invert_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.invert = sys::variable_item_get_current_value_index(item) == 1;
+    sys::variable_item_set_current_value_text(item, on_off(app.settings.invert));
+}
+
+pub unsafe extern "C" fn set_retries_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.retries = sys::variable_item_get_current_value_index(item) as u32;
+    let mut text = FuriString::new();
+    let _ = uwrite!(text, "{}", app.settings.retries);
+    sys::variable_item_set_current_value_text(item, text.as_c_str().as_ptr());
+}
+
+pub unsafe extern "C" fn set_batch_failures_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.batch_skip_failed = sys::variable_item_get_current_value_index(item) == 1;
+    let text = if app.settings.batch_skip_failed {
+        c_string!("Skip")
+    } else {
+        c_string!("Ask")
+    };
+    sys::variable_item_set_current_value_text(item, text);
+}
+
+pub unsafe extern "C" fn set_capture_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.capture = sys::variable_item_get_current_value_index(item) == 1;
+    sys::variable_item_set_current_value_text(item, on_off(app.settings.capture));
+}
+
+pub unsafe extern "C" fn set_console_crc_callback(item: *mut sys::VariableItem) {
+    let app = app(sys::variable_item_get_context(item));
+    app.settings.console_crc = sys::variable_item_get_current_value_index(item) == 1;
+    sys::variable_item_set_current_value_text(item, on_off(app.settings.console_crc));
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let variable_item_list = app.variable_item_list.as_ptr();
+    let settings = &app.settings;
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Tag Size"),
+        TagSize::ALL.len() as u8,
+        Some(set_tag_size_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, settings.tag_size as u8);
+    sys::variable_item_set_current_value_text(item, settings.tag_size.text().as_ptr());
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Rotation"),
+        Rotation::ALL.len() as u8,
+        Some(set_rotation_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, settings.rotation as u8);
+    sys::variable_item_set_current_value_text(item, settings.rotation.text().as_ptr());
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Dithering"),
+        2,
+        Some(set_dithering_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, settings.dithering as u8);
+    sys::variable_item_set_current_value_text(item, on_off(settings.dithering));
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Invert"),
+        2,
+        Some(set_invert_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, settings.invert as u8);
+    sys::variable_item_set_current_value_text(item, on_off(settings.invert));
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Retries"),
+        MAX_RETRIES + 1,
+        Some(set_retries_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, settings.retries.min(MAX_RETRIES as u32) as u8);
+    set_retries_callback(item);
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Batch Failures"),
+        2,
+        Some(set_batch_failures_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.settings.batch_skip_failed as u8);
+    set_batch_failures_callback(item);
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Capture"),
+        2,
+        Some(set_capture_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.settings.capture as u8);
+    set_capture_callback(item);
+
+    let item = sys::variable_item_list_add(
+        variable_item_list,
+        c_string!("Console CRC"),
+        2,
+        Some(set_console_crc_callback),
+        context,
+    );
+    sys::variable_item_set_current_value_index(item, app.settings.console_crc as u8);
+    set_console_crc_callback(item);
+
+    app.switch_to_view(AppView::VariableItemList);
+}
+
+pub unsafe extern "C" fn on_event(_context: *mut c_void, _event: sys::SceneManagerEvent) -> bool {
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    app.settings.save();
+    sys::variable_item_list_reset(app.variable_item_list.as_ptr());
+}
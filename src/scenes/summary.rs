@@ -0,0 +1,63 @@
+use core::ffi::c_void;
+use flipperzero::furi::string::FuriString;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, Scene};
+use crate::AppView;
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let widget = app.widget.as_ptr();
+
+    let mut written = FuriString::new();
+    let mut skipped = FuriString::new();
+    if let Some(batch) = &app.batch {
+        let _ = uwrite!(written, "wrote {} of {}", batch.written, batch.len());
+        let _ = uwrite!(skipped, "skipped {}", batch.skipped);
+    }
+
+    sys::widget_reset(widget);
+    sys::widget_add_string_element(
+        widget,
+        64,
+        12,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontPrimary,
+        c_string!("batch done"),
+    );
+    sys::widget_add_string_element(
+        widget,
+        64,
+        32,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontSecondary,
+        written.as_c_str().as_ptr(),
+    );
+    sys::widget_add_string_element(
+        widget,
+        64,
+        44,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+        sys::Font_FontSecondary,
+        skipped.as_c_str().as_ptr(),
+    );
+    app.switch_to_view(AppView::Widget);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        return app.back_to_scene(Scene::MainMenu);
+    }
+    false
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
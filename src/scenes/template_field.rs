@@ -0,0 +1,69 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::image::Image;
+use crate::template::INPUT_SIZE;
+use crate::{App, AppView};
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::FieldEntered);
+}
+
+/// Asks for the current placeholder, named in the header.
+unsafe fn ask(app: &mut App, context: *mut c_void) {
+    let Some(form) = &mut app.form else {
+        return;
+    };
+    let text_input = app.text_input.as_ptr();
+    form.edit();
+    app.text.clear();
+    let _ = uwrite!(app.text, "{}", form.placeholder().unwrap_or(""));
+    sys::text_input_reset(text_input);
+    sys::text_input_set_header_text(text_input, app.text.as_c_str().as_ptr());
+    sys::text_input_set_minimum_length(text_input, 0);
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        form.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    ask(app, context);
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if custom_event(event) != Some(AppEvent::FieldEntered) {
+        return false;
+    }
+    let Some(form) = &mut app.form else {
+        return true;
+    };
+    form.save();
+    if form.placeholder().is_some() {
+        ask(app, context);
+        return true;
+    }
+    match form.render(&app.settings) {
+        Ok(framebuffer) => {
+            app.image = Some(Image::from_framebuffer(framebuffer, "template"));
+            app.next_scene(Scene::Waiting);
+        }
+        Err(message) => app.show_error(message),
+    }
+    true
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+}
@@ -0,0 +1,94 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::batch::Batch;
+use crate::image::Image;
+use crate::merge::Merge;
+use crate::template::{Form, Template};
+use crate::AppView;
+
+pub unsafe extern "C" fn submenu_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    app.set_scene_state(Scene::TemplatePick, index);
+    app.send_event(AppEvent::TemplatePicked);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let submenu = app.submenu.as_ptr();
+
+    app.templates = Template::list();
+    if app.templates.is_empty() {
+        app.show_error(c_string!("No templates found"));
+        return;
+    }
+    sys::submenu_set_header(submenu, c_string!("Templates"));
+    for (index, name) in app.templates.iter().enumerate() {
+        sys::submenu_add_item(
+            submenu,
+            name.as_c_str().as_ptr(),
+            index as u32,
+            Some(submenu_callback),
+            context,
+        );
+    }
+    sys::submenu_set_selected_item(submenu, app.scene_state(Scene::TemplatePick));
+    app.switch_to_view(AppView::Submenu);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if custom_event(event) != Some(AppEvent::TemplatePicked) {
+        return false;
+    }
+    let Some(name) = app
+        .templates
+        .get(app.scene_state(Scene::TemplatePick) as usize)
+    else {
+        return true;
+    };
+    if let Some(csv_path) = &app.csv_path {
+        match Merge::open(name.as_c_str(), csv_path.as_c_str(), app.settings.tag_size) {
+            Ok(merge) => {
+                let resume = merge.written() > 0;
+                app.batch = Some(Batch::from_merge(merge));
+                app.next_scene(if resume {
+                    Scene::MergeResume
+                } else {
+                    Scene::Waiting
+                });
+            }
+            Err(message) => app.show_error(message),
+        }
+        return true;
+    }
+    match Form::open(name.as_c_str(), app.form.take()) {
+        Ok(form) => app.form = Some(form),
+        Err(message) => {
+            app.show_error(message);
+            return true;
+        }
+    }
+    let Some(form) = &app.form else {
+        return true;
+    };
+    if form.placeholder().is_some() {
+        app.next_scene(Scene::TemplateField);
+        return true;
+    }
+    match form.render(&app.settings) {
+        Ok(framebuffer) => {
+            app.image = Some(Image::from_framebuffer(framebuffer, "template"));
+            app.next_scene(Scene::Waiting);
+        }
+        Err(message) => app.show_error(message),
+    }
+    true
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::submenu_reset(app.submenu.as_ptr());
+}
@@ -0,0 +1,113 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::image::Image;
+use crate::label::MAX_LINES;
+use crate::{App, AppView};
+
+/// Submenu items after the lines, which use their index.
+const ADD_LINE: u32 = 100;
+const ALIGN: u32 = 101;
+const WRITE: u32 = 102;
+const FONT: u32 = 103;
+
+pub unsafe extern "C" fn submenu_callback(context: *mut c_void, index: u32) {
+    let app = app(context);
+    app.set_scene_state(Scene::TextLabel, index);
+    app.send_event(AppEvent::LabelItem);
+}
+
+unsafe fn fill_menu(app: &mut App) {
+    let submenu = app.submenu.as_ptr();
+    let context = app as *mut App as *mut c_void;
+    sys::submenu_reset(submenu);
+    sys::submenu_set_header(submenu, c_string!("Text Label"));
+    for (index, line) in app.label.lines().iter().enumerate() {
+        sys::submenu_add_item(
+            submenu,
+            line.as_ptr(),
+            index as u32,
+            Some(submenu_callback),
+            context,
+        );
+    }
+    if app.label.lines().len() < MAX_LINES {
+        sys::submenu_add_item(
+            submenu,
+            c_string!("+ Add Line"),
+            ADD_LINE,
+            Some(submenu_callback),
+            context,
+        );
+    }
+    sys::submenu_add_item(
+        submenu,
+        app.label.align.text(),
+        ALIGN,
+        Some(submenu_callback),
+        context,
+    );
+    sys::submenu_add_item(
+        submenu,
+        app.label.font_text().as_c_str().as_ptr(),
+        FONT,
+        Some(submenu_callback),
+        context,
+    );
+    sys::submenu_add_item(
+        submenu,
+        c_string!("Write"),
+        WRITE,
+        Some(submenu_callback),
+        context,
+    );
+    sys::submenu_set_selected_item(submenu, app.scene_state(Scene::TextLabel));
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    app.label.find_fonts();
+    fill_menu(app);
+    app.switch_to_view(AppView::Submenu);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if custom_event(event) != Some(AppEvent::LabelItem) {
+        return false;
+    }
+    match app.scene_state(Scene::TextLabel) {
+        ADD_LINE => {
+            app.set_scene_state(Scene::TextLine, app.label.lines().len() as u32);
+            app.next_scene(Scene::TextLine);
+        }
+        ALIGN => {
+            app.label.align = app.label.align.next();
+            fill_menu(app);
+        }
+        FONT => {
+            app.label.next_font();
+            fill_menu(app);
+        }
+        WRITE if app.label.lines().is_empty() => app.show_error(c_string!("No text to write")),
+        WRITE => match app.label.render(&app.settings) {
+            Ok(framebuffer) => {
+                app.image = Some(Image::from_framebuffer(framebuffer, "text label"));
+                app.next_scene(Scene::Waiting);
+            }
+            Err(message) => app.show_error(message),
+        },
+        line => {
+            app.set_scene_state(Scene::TextLine, line);
+            app.next_scene(Scene::TextLine);
+        }
+    }
+    true
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::submenu_reset(app.submenu.as_ptr());
+}
@@ -0,0 +1,46 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::label::INPUT_SIZE;
+use crate::AppView;
+
+pub unsafe extern "C" fn text_input_callback(context: *mut c_void) {
+    let app = app(context);
+    app.send_event(AppEvent::LineEntered);
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let text_input = app.text_input.as_ptr();
+    app.label.edit(app.scene_state(Scene::TextLine) as usize);
+    sys::text_input_set_header_text(text_input, c_string!("Line, empty removes it"));
+    sys::text_input_set_minimum_length(text_input, 0);
+    sys::text_input_set_result_callback(
+        text_input,
+        Some(text_input_callback),
+        context,
+        app.label.input.as_mut_ptr(),
+        INPUT_SIZE,
+        false,
+    );
+    app.switch_to_view(AppView::TextInput);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::LineEntered) => {
+            app.label.save(app.scene_state(Scene::TextLine) as usize);
+            app.back_to_scene(Scene::TextLabel);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::text_input_reset(app.text_input.as_ptr());
+}
@@ -0,0 +1,81 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, waiting, AppEvent, Scene};
+use crate::AppView;
+
+pub unsafe extern "C" fn dialog_callback(result: sys::DialogExResult, context: *mut c_void) {
+    let app = app(context);
+    match result {
+        sys::DialogExResult_DialogExResultLeft => app.send_event(AppEvent::Skip),
+        sys::DialogExResult_DialogExResultRight => app.send_event(AppEvent::Force),
+        _ => {}
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    let dialog_ex = app.dialog_ex.as_ptr();
+
+    sys::dialog_ex_set_header(
+        dialog_ex,
+        c_string!("Already up to date"),
+        64,
+        0,
+        sys::Align_AlignCenter,
+        sys::Align_AlignTop,
+    );
+    sys::dialog_ex_set_text(
+        dialog_ex,
+        c_string!("Tag shows this image"),
+        64,
+        32,
+        sys::Align_AlignCenter,
+        sys::Align_AlignCenter,
+    );
+    sys::dialog_ex_set_left_button_text(dialog_ex, c_string!("Skip"));
+    sys::dialog_ex_set_right_button_text(dialog_ex, c_string!("Force"));
+    sys::dialog_ex_set_result_callback(dialog_ex, Some(dialog_callback));
+    sys::dialog_ex_set_context(dialog_ex, context);
+
+    app.switch_to_view(AppView::DialogEx);
+}
+
+/// Leaves the tag as it is and moves on to the next one, or back to the preview or label.
+fn skip(app: &mut crate::App) {
+    if let Some(batch) = &mut app.batch {
+        batch.skipped += 1;
+        batch.advance();
+    }
+    if app.batch.is_some() || app.mappings.is_some() {
+        app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+        app.back_to_scene(Scene::Waiting);
+    } else {
+        app.back_to_source();
+    }
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        skip(app);
+        return true;
+    }
+    match custom_event(event) {
+        Some(AppEvent::Skip) => {
+            skip(app);
+            true
+        }
+        Some(AppEvent::Force) => {
+            app.next_scene(Scene::Writing);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::dialog_ex_reset(app.dialog_ex.as_ptr());
+}
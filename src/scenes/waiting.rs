@@ -0,0 +1,156 @@
+use core::ffi::{c_char, c_void};
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use super::{app, custom_event, AppEvent, Scene};
+use crate::image;
+use crate::writer;
+use crate::{update_widget, App, AppView};
+
+/// Scene states, waiting for a tag or for the last written tag to be taken away.
+pub const PRESENT: u32 = 0;
+pub const REMOVED: u32 = 1;
+
+/// Loads the next image of a batch that fits a panel, or the next merged row that renders,
+/// skipping the others. Returns false when the batch is finished.
+fn load_batch_image(app: &mut App) -> bool {
+    let Some(batch) = &mut app.batch else {
+        return false;
+    };
+    while !batch.is_done() {
+        match batch.load(&mut app.settings) {
+            Ok(image) => {
+                app.image = Some(image);
+                return true;
+            }
+            Err(_) => {
+                println!("skipping {}", batch.position());
+                batch.skipped += 1;
+                batch.advance();
+            }
+        }
+    }
+    false
+}
+
+/// Loads the image mapped to the tag's UID, applying its panel and rotation overrides.
+fn load_mapped_image(app: &mut App, uid: &[u8]) -> Result<(), *const c_char> {
+    let Some(mapping) = app.mappings.as_ref().and_then(|mappings| mappings.find(uid)) else {
+        return Err(c_string!("unknown tag"));
+    };
+    let file_path = mapping.file_path.as_c_str();
+    let (rotation, tag_size) = (app.settings.rotation, app.settings.tag_size);
+    app.settings.rotation = mapping.rotation.unwrap_or(rotation);
+    let loaded = match mapping.tag_size {
+        Some(tag_size) => Ok(tag_size),
+        None => image::detect_tag_size(file_path, app.settings.rotation),
+    }
+    .and_then(|tag_size| {
+        app.settings.tag_size = tag_size;
+        image::load(file_path, &app.settings)
+    });
+    // only override them for this tag, the image keeps its panel for the write
+    app.settings.rotation = rotation;
+    app.settings.tag_size = tag_size;
+    app.image = Some(loaded.map_err(|error| error.message())?);
+    Ok(())
+}
+
+fn show_label(app: &App) {
+    let mut label = FuriString::new();
+    if let Some(batch) = &app.batch {
+        let _ = uwrite!(
+            label,
+            "label {}/{}: {}",
+            batch.position(),
+            batch.len(),
+            batch.name()
+        );
+    } else if let Some(mappings) = &app.mappings {
+        let _ = uwrite!(label, "sweep: {} written", mappings.written);
+    } else {
+        return;
+    }
+    let widget = app.widget.as_ptr();
+    unsafe {
+        sys::widget_add_string_element(
+            widget,
+            64,
+            12,
+            sys::Align_AlignCenter,
+            sys::Align_AlignCenter,
+            sys::Font_FontSecondary,
+            label.as_c_str().as_ptr(),
+        );
+    }
+}
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    if app.batch.is_some() && !load_batch_image(app) {
+        app.next_scene(Scene::Summary);
+        return;
+    }
+    update_widget(app.widget.as_ptr(), c_string!("waiting for tag"));
+    show_label(app);
+    app.switch_to_view(AppView::Widget);
+    app.send_event(AppEvent::WaitForTag);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    match custom_event(event) {
+        Some(AppEvent::WaitForTag) => {
+            let found = writer::detect(&app.settings);
+            if app.scene_state(Scene::Waiting) == REMOVED {
+                if found.is_none() {
+                    app.set_scene_state(Scene::Waiting, PRESENT);
+                }
+            } else if let Some(tag) = found {
+                println!("found tag");
+                app.tag = Some(tag);
+                let uid = tag.uid();
+                if app.mappings.is_none() {
+                    app.send_event(AppEvent::TagFound);
+                    return true;
+                }
+                match load_mapped_image(app, uid) {
+                    Ok(()) => app.send_event(AppEvent::TagFound),
+                    Err(message) => {
+                        // tell the user and wait for the next tag
+                        update_widget(app.widget.as_ptr(), message);
+                        show_label(app);
+                        app.set_scene_state(Scene::Waiting, REMOVED);
+                        app.send_event(AppEvent::WaitForTag);
+                    }
+                }
+                return true;
+            }
+
+            sys::furi_delay_ms(50);
+            app.send_event(AppEvent::WaitForTag); // run wait for tag event again
+            true
+        }
+        Some(AppEvent::TagFound) => {
+            let up_to_date = match (&app.image, &app.tag) {
+                (Some(image), Some(tag)) => writer::is_up_to_date(image, tag, &app.cache),
+                _ => false,
+            };
+            if up_to_date {
+                app.next_scene(Scene::UpToDate);
+            } else {
+                app.next_scene(Scene::Writing);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
@@ -0,0 +1,79 @@
+use core::ffi::c_void;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use super::{app, custom_event, waiting, AppEvent, Scene};
+use crate::writer::{self, WidgetProgress};
+use crate::{update_widget, AppView};
+
+pub unsafe extern "C" fn on_enter(context: *mut c_void) {
+    let app = app(context);
+    update_widget(app.widget.as_ptr(), c_string!("setting up"));
+    app.switch_to_view(AppView::Widget);
+    app.send_event(AppEvent::StartWrite);
+}
+
+pub unsafe extern "C" fn on_event(context: *mut c_void, event: sys::SceneManagerEvent) -> bool {
+    let app = app(context);
+    if event.type_ == sys::SceneManagerEventType_SceneManagerEventTypeBack {
+        return true; // don't leave while the tag is being written
+    }
+    match custom_event(event) {
+        Some(AppEvent::StartWrite) => {
+            let Some(image) = app.image.take() else {
+                app.show_error(c_string!("No file selected"));
+                return true;
+            };
+            let Some(tag) = app.tag else {
+                app.show_error(c_string!("Tag lost"));
+                return true;
+            };
+            // the poller stopped once the tag was detected, so the write selects it again
+            let mut progress = WidgetProgress(app.widget.as_ptr());
+            let result = writer::write_and_log(
+                &image,
+                &app.settings,
+                &tag,
+                &mut app.cache,
+                &mut progress,
+            );
+            match (result, &mut app.batch) {
+                (Ok(()), Some(batch)) => {
+                    batch.mark_written();
+                    app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+                    app.back_to_scene(Scene::Waiting);
+                }
+                (Err(_), Some(batch)) if app.settings.batch_skip_failed => {
+                    batch.skipped += 1;
+                    batch.advance();
+                    app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+                    app.back_to_scene(Scene::Waiting);
+                }
+                (Err(error), Some(_)) => {
+                    app.error_message = error.message();
+                    app.next_scene(Scene::BatchFailed);
+                }
+                (Ok(()), None) if app.mappings.is_some() => {
+                    if let Some(mappings) = &mut app.mappings {
+                        mappings.written += 1;
+                    }
+                    app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+                    app.back_to_scene(Scene::Waiting);
+                }
+                (Err(error), None) if app.mappings.is_some() => {
+                    app.set_scene_state(Scene::Waiting, waiting::REMOVED);
+                    app.show_error(error.message());
+                }
+                (Ok(()), None) => app.next_scene(Scene::Result),
+                (Err(error), None) => app.show_error(error.message()),
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+pub unsafe extern "C" fn on_exit(context: *mut c_void) {
+    let app = app(context);
+    sys::widget_reset(app.widget.as_ptr());
+}
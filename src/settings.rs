@@ -0,0 +1,188 @@
+use core::ffi::{c_char, CStr};
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+
+use crate::driver::Timing;
+use crate::framebuffer::Rotation;
+use crate::tag::TagSize;
+
+/// Keys are only ever added, files of older versions are read by leaving the keys they lack at
+/// their default. Bump only when a key changes meaning, and branch on the version in `read`.
+const VERSION: u32 = 2;
+const FILE_TYPE: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"Waveshare Settings\0") };
+
+pub fn app_data_dir() -> *const c_char {
+    c_string!("/ext/apps_data/waveshare")
+}
+
+fn settings_path() -> *const c_char {
+    c_string!("/ext/apps_data/waveshare/settings.txt")
+}
+
+pub struct Settings {
+    pub tag_size: TagSize,
+    pub file_path: Option<FuriString>,
+    pub rotation: Rotation,
+    pub dithering: bool,
+    pub invert: bool,
+    /// ms to wait for a response to each frame
+    pub tx_timeout: u32,
+    /// ms to wait for a tag on each detect attempt
+    pub detect_timeout: u32,
+    /// ms to wait for the panel to finish refreshing
+    pub refresh_timeout: u32,
+    /// times to resend a frame the tag didn't acknowledge
+    pub retries: u32,
+    /// skip images that fail in a batch instead of asking
+    pub batch_skip_failed: bool,
+    /// log every frame of a write to a pcap file
+    pub capture: bool,
+    /// add and check the CRC of console frames, off to send and show them raw
+    pub console_crc: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            tag_size: TagSize::TwoNine,
+            file_path: None,
+            rotation: Rotation::None,
+            dithering: true,
+            invert: false,
+            tx_timeout: 300,
+            detect_timeout: 300,
+            refresh_timeout: 5000,
+            retries: 2,
+            batch_skip_failed: false,
+            capture: false,
+            console_crc: true,
+        }
+    }
+}
+
+unsafe fn read_u32(ff: *mut sys::FlipperFormat, key: *const c_char, value: &mut u32) -> bool {
+    sys::flipper_format_rewind(ff);
+    sys::flipper_format_read_uint32(ff, key, value, 1)
+}
+
+unsafe fn read_bool(ff: *mut sys::FlipperFormat, key: *const c_char, value: &mut bool) -> bool {
+    sys::flipper_format_rewind(ff);
+    sys::flipper_format_read_bool(ff, key, value, 1)
+}
+
+impl Settings {
+    pub fn timing(&self) -> Timing {
+        Timing {
+            tx_timeout: self.tx_timeout,
+            retries: self.retries,
+            refresh_timeout: self.refresh_timeout,
+        }
+    }
+
+    /// Loads settings from the SD card, using defaults for anything missing or unreadable.
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        unsafe {
+            let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+            let ff = sys::flipper_format_file_alloc(storage);
+            let mut file_type = FuriString::new();
+            let mut version = 0;
+            if sys::flipper_format_file_open_existing(ff, settings_path())
+                && sys::flipper_format_read_header(ff, file_type.as_mut_ptr(), &mut version)
+                && file_type.as_c_str() == FILE_TYPE
+                && version <= VERSION
+            {
+                settings.read(ff);
+            } else {
+                println!("using default settings");
+            }
+            sys::flipper_format_free(ff);
+            sys::furi_record_close(c_string!("storage"));
+        }
+        settings
+    }
+
+    unsafe fn read(&mut self, ff: *mut sys::FlipperFormat) {
+        // keys missing from older files keep their default, e.g. Batch Skip Failed and Capture
+        let mut value = 0;
+        if read_u32(ff, c_string!("Tag Size"), &mut value) {
+            self.tag_size = TagSize::from_index(value as u8).unwrap_or(self.tag_size);
+        }
+        if read_u32(ff, c_string!("Rotation"), &mut value) {
+            self.rotation = Rotation::from_index(value as u8).unwrap_or(self.rotation);
+        }
+        read_bool(ff, c_string!("Dithering"), &mut self.dithering);
+        read_bool(ff, c_string!("Invert"), &mut self.invert);
+        read_u32(ff, c_string!("Tx Timeout"), &mut self.tx_timeout);
+        read_u32(ff, c_string!("Detect Timeout"), &mut self.detect_timeout);
+        read_u32(ff, c_string!("Refresh Timeout"), &mut self.refresh_timeout);
+        read_u32(ff, c_string!("Retries"), &mut self.retries);
+        read_bool(ff, c_string!("Batch Skip Failed"), &mut self.batch_skip_failed);
+        read_bool(ff, c_string!("Capture"), &mut self.capture);
+        read_bool(ff, c_string!("Console CRC"), &mut self.console_crc);
+
+        let mut file_path = FuriString::new();
+        sys::flipper_format_rewind(ff);
+        if sys::flipper_format_read_string(ff, c_string!("Last File"), file_path.as_mut_ptr())
+            && !file_path.as_c_str().is_empty()
+        {
+            self.file_path = Some(file_path);
+        }
+    }
+
+    /// Saves settings to the SD card, creating the app data folder if needed.
+    pub fn save(&self) {
+        unsafe {
+            let storage = sys::furi_record_open(c_string!("storage")) as *mut sys::Storage;
+            sys::storage_simply_mkdir(storage, app_data_dir());
+            let ff = sys::flipper_format_file_alloc(storage);
+            let tag_size = self.tag_size as u32;
+            let rotation = self.rotation as u32;
+            let file_path = match &self.file_path {
+                Some(file_path) => file_path.as_c_str().as_ptr(),
+                None => c_string!(""),
+            };
+            let ok = sys::flipper_format_file_open_always(ff, settings_path())
+                && sys::flipper_format_write_header_cstr(ff, FILE_TYPE.as_ptr(), VERSION)
+                && sys::flipper_format_write_uint32(ff, c_string!("Tag Size"), &tag_size, 1)
+                && sys::flipper_format_write_string_cstr(ff, c_string!("Last File"), file_path)
+                && sys::flipper_format_write_uint32(ff, c_string!("Rotation"), &rotation, 1)
+                && sys::flipper_format_write_bool(ff, c_string!("Dithering"), &self.dithering, 1)
+                && sys::flipper_format_write_bool(ff, c_string!("Invert"), &self.invert, 1)
+                && sys::flipper_format_write_uint32(ff, c_string!("Tx Timeout"), &self.tx_timeout, 1)
+                && sys::flipper_format_write_uint32(
+                    ff,
+                    c_string!("Detect Timeout"),
+                    &self.detect_timeout,
+                    1,
+                )
+                && sys::flipper_format_write_uint32(
+                    ff,
+                    c_string!("Refresh Timeout"),
+                    &self.refresh_timeout,
+                    1,
+                )
+                && sys::flipper_format_write_uint32(ff, c_string!("Retries"), &self.retries, 1)
+                && sys::flipper_format_write_bool(
+                    ff,
+                    c_string!("Batch Skip Failed"),
+                    &self.batch_skip_failed,
+                    1,
+                )
+                && sys::flipper_format_write_bool(ff, c_string!("Capture"), &self.capture, 1)
+                && sys::flipper_format_write_bool(
+                    ff,
+                    c_string!("Console CRC"),
+                    &self.console_crc,
+                    1,
+                );
+            if !ok {
+                println!("couldn't save settings");
+            }
+            sys::flipper_format_free(ff);
+            sys::furi_record_close(c_string!("storage"));
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! A model of a Waveshare tag answering the 0xCD commands in `tag.rs`. Has no Flipper
+//! dependencies so the host tools can build it too.
+
+use alloc::vec::Vec;
+
+use crate::tag::TagSize;
+
+const CMD: u8 = 0xCD;
+
+/// Identity the tag answers anticollision with, a triple size UID starting with the `WSDZ10m`
+/// the drivers look for.
+pub const UID: [u8; 10] = *b"WSDZ10m\x00\x00\x01";
+pub const ATQA: [u8; 2] = [0x84, 0x00];
+pub const SAK: u8 = 0x00;
+
+pub const ACK: &[u8] = &[0x00, 0x00];
+pub const READY: &[u8] = &[0xFF, 0x00];
+/// What a wait poll gets while the panel is still refreshing.
+pub const BUSY: &[u8] = &[0x00, 0x00];
+
+/// Wait polls answered as busy after a refresh, like a real panel taking a while.
+const REFRESH_POLLS: u32 = 3;
+
+pub struct SimTag {
+    tag_size: Option<TagSize>,
+    /// Panel memory being loaded, set bits are white.
+    buffer: Vec<u8>,
+    /// What the panel shows after the last finished refresh.
+    shown: Option<(TagSize, Vec<u8>)>,
+    refresh_polls: Option<u32>,
+}
+
+impl SimTag {
+    pub fn new() -> Self {
+        Self {
+            tag_size: None,
+            buffer: Vec::new(),
+            shown: None,
+            refresh_polls: None,
+        }
+    }
+
+    /// Handles one frame, returning the response or `None` if the tag stays silent.
+    pub fn exchange(&mut self, frame: &[u8]) -> Option<&'static [u8]> {
+        let [CMD, opcode, args @ ..] = frame else {
+            return None;
+        };
+        match (opcode, args) {
+            (0x00, [id]) => {
+                self.tag_size = TagSize::ALL.into_iter().find(|size| size.id() == *id);
+                Some(ACK)
+            }
+            (0x01..=0x06 | 0x0D | 0x18, _) => Some(ACK),
+            (0x07, _) => {
+                self.buffer.clear();
+                Some(ACK)
+            }
+            (0x08, [len, data @ ..]) if *len as usize == data.len() => {
+                let tag_size = self.tag_size?;
+                if self.buffer.len() + data.len() > tag_size.width() * tag_size.height() / 8 {
+                    return None; // more data than the panel holds
+                }
+                self.buffer.extend_from_slice(data);
+                Some(ACK)
+            }
+            (0x09, _) => {
+                self.tag_size?;
+                self.refresh_polls = Some(REFRESH_POLLS);
+                Some(ACK)
+            }
+            (0x0A, _) => match self.refresh_polls {
+                Some(0) => {
+                    self.refresh_polls = None;
+                    let tag_size = self.tag_size?;
+                    self.shown = Some((tag_size, self.buffer.clone()));
+                    Some(READY)
+                }
+                Some(polls) => {
+                    self.refresh_polls = Some(polls - 1);
+                    Some(BUSY)
+                }
+                None => Some(READY),
+            },
+            _ => None,
+        }
+    }
+
+    /// The panel size and memory shown after the last refresh, set bits are white.
+    pub fn shown(&self) -> Option<(TagSize, &[u8])> {
+        let (tag_size, data) = self.shown.as_ref()?;
+        Some((*tag_size, data))
+    }
+
+    /// What the panel shows as a PBM file, where set bits are black. Memory the write didn't
+    /// fill is left white.
+    pub fn pbm(&self) -> Option<Vec<u8>> {
+        let (tag_size, data) = self.shown()?;
+        let mut pbm = Vec::from(&tag_size.header()[..]);
+        pbm.extend(data.iter().map(|byte| !byte));
+        pbm.resize(
+            tag_size.header().len() + tag_size.width() * tag_size.height() / 8,
+            0,
+        );
+        Some(pbm)
+    }
+}
+
+/// What a `LoggedTag` was sent, in order.
+pub enum Entry {
+    /// A command received `ms` after the reader started, and the answer if any.
+    Command {
+        ms: u32,
+        frame: Vec<u8>,
+        response: Option<&'static [u8]>,
+    },
+    /// A run of data chunks, too many to log one by one.
+    Chunks(usize),
+}
+
+/// A tag answering a reader that logs the commands it gets, with runs of data chunks counted.
+pub struct LoggedTag {
+    pub tag: SimTag,
+    pub log: Vec<Entry>,
+    chunks: usize,
+}
+
+impl LoggedTag {
+    pub fn new() -> Self {
+        Self {
+            tag: SimTag::new(),
+            log: Vec::new(),
+            chunks: 0,
+        }
+    }
+
+    /// Logs the data chunks received since the last other command.
+    pub fn flush_chunks(&mut self) {
+        if self.chunks > 0 {
+            self.log.push(Entry::Chunks(self.chunks));
+            self.chunks = 0;
+        }
+    }
+
+    /// Answers a frame received `ms` after the reader started, like `SimTag::exchange`.
+    pub fn handle(&mut self, frame: &[u8], ms: u32) -> Option<&'static [u8]> {
+        let response = self.tag.exchange(frame);
+        if let [CMD, 0x08, ..] = frame {
+            self.chunks += 1;
+            return response;
+        }
+
+        self.flush_chunks();
+        self.log.push(Entry::Command {
+            ms,
+            frame: frame.into(),
+            response,
+        });
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{self, Link, Progress, Status, Timing};
+    use crate::transport::{TagInfo, Transport};
+
+    /// The log as the emulation saves it, `<ms> > <frame> < <response>` a command.
+    fn lines(log: &[Entry]) -> Vec<String> {
+        let hex = |bytes: &[u8]| {
+            let bytes: Vec<String> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+            bytes.join(" ")
+        };
+        log.iter()
+            .map(|entry| match entry {
+                Entry::Command {
+                    ms,
+                    frame,
+                    response,
+                } => {
+                    let response = response.map_or("-".into(), hex);
+                    format!("{ms} > {} < {response}", hex(frame))
+                }
+                Entry::Chunks(count) => format!("{count} data chunks"),
+            })
+            .collect()
+    }
+
+    /// A reader whose frames reach the tag the way the emulation's listener hands them over.
+    struct Reader<'a>(&'a mut LoggedTag);
+
+    impl Transport for Reader<'_> {
+        fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], _timeout: u32) -> Option<usize> {
+            let response = self.0.handle(tx, 0)?;
+            rx[..response.len()].copy_from_slice(response);
+            Some(response.len() * 8)
+        }
+
+        fn delay_ms(&mut self, _ms: u32) {}
+    }
+
+    struct NoProgress;
+
+    impl Progress for NoProgress {
+        fn update(&mut self, _status: Status) {}
+    }
+
+    #[test]
+    fn logs_a_driver_write() {
+        let tag_size = TagSize::TwoNine;
+        let plane: Vec<u8> = (0..tag_size.width() * tag_size.height() / 8)
+            .map(|i| i as u8)
+            .collect();
+        let mut tag = LoggedTag::new();
+
+        let driver = driver::find(&TagInfo::new(&UID, ATQA, SAK)).expect("no driver for the tag");
+        let timing = Timing {
+            tx_timeout: 300,
+            retries: 0,
+            refresh_timeout: 5000,
+        };
+        let mut reader = Reader(&mut tag);
+        let mut link = Link::new(&mut reader, timing);
+        let result = driver::write(driver, &mut link, tag_size, &[&plane], &mut NoProgress);
+        assert!(result.is_ok());
+
+        assert_eq!(
+            lines(&tag.log),
+            [
+                "0 > CD 0D < 00 00",
+                "0 > CD 00 07 < 00 00",
+                "0 > CD 01 < 00 00",
+                "0 > CD 02 < 00 00",
+                "0 > CD 03 < 00 00",
+                "0 > CD 05 < 00 00",
+                "0 > CD 06 < 00 00",
+                "0 > CD 07 00 < 00 00",
+                "296 data chunks",
+                "0 > CD 18 < 00 00",
+                "0 > CD 09 < 00 00",
+                "0 > CD 0A < 00 00",
+                "0 > CD 0A < 00 00",
+                "0 > CD 0A < 00 00",
+                "0 > CD 0A < FF 00",
+                "0 > CD 04 < 00 00",
+            ]
+        );
+
+        let mut pbm = Vec::from(&tag_size.header()[..]);
+        pbm.extend_from_slice(&plane);
+        assert_eq!(tag.tag.pbm(), Some(pbm));
+    }
+
+    #[test]
+    fn logs_unanswered_frames_after_chunks() {
+        let mut tag = LoggedTag::new();
+        tag.handle(&[0xCD, 0x00, 0x07], 5);
+        assert_eq!(tag.handle(&[0xCD, 0x08, 0x01, 0xAA], 6), Some(ACK));
+        assert_eq!(tag.handle(&[0xCD, 0x08, 0x01, 0xBB], 7), Some(ACK));
+        assert_eq!(tag.handle(&[0x30, 0x04], 12), None);
+        assert_eq!(
+            lines(&tag.log),
+            ["5 > CD 00 07 < 00 00", "2 data chunks", "12 > 30 04 < -"]
+        );
+    }
+}
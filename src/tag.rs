@@ -1,12 +1,10 @@
 use alloc::vec;
 use alloc::vec::Vec;
-use core::ffi::c_char;
-use flipperzero_sys as sys;
-use sys::c_string;
+use core::ffi::CStr;
 
 static CMD: u8 = 0xCD;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TagSize {
     // must match location in menu
     TwoNine = 0,
@@ -15,11 +13,36 @@ pub enum TagSize {
 }
 
 impl TagSize {
-    pub fn text(&self) -> *const c_char {
+    pub const ALL: [TagSize; 3] = [Self::TwoNine, Self::FourTwo, Self::SevenFive];
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Parses a panel name as given in launch arguments, e.g. `4.2` or `4.2"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim_end_matches('"') {
+            "2.9" => Some(Self::TwoNine),
+            "4.2" => Some(Self::FourTwo),
+            "7.5" => Some(Self::SevenFive),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::TwoNine => c_string!("2.9\""),
-            Self::FourTwo => c_string!("4.2\""),
-            Self::SevenFive => c_string!("7.5\""),
+            Self::TwoNine => "2.9",
+            Self::FourTwo => "4.2",
+            Self::SevenFive => "7.5",
+        }
+    }
+
+    // no sys macros here, the host tools build this file too
+    pub fn text(&self) -> &'static CStr {
+        match self {
+            Self::TwoNine => c"2.9\"",
+            Self::FourTwo => c"4.2\"",
+            Self::SevenFive => c"7.5\"",
         }
     }
 
@@ -84,6 +107,17 @@ impl TagSize {
         (buffer, preamble.len())
     }
 
+    /// Splits a packed image into the data of each chunk command, inverted since the panel takes
+    /// 1 = white.
+    pub fn chunks<'a>(
+        &self,
+        plane: &'a [u8],
+    ) -> impl Iterator<Item = impl Iterator<Item = u8> + 'a> + 'a {
+        plane
+            .chunks(self.chunk_size() as usize)
+            .map(|chunk| chunk.iter().map(|byte| !byte))
+    }
+
     pub fn loops(&self) -> usize {
         (self.width() * self.height()) / (self.chunk_size() as usize * 8)
     }
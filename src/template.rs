@@ -0,0 +1,414 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{
+    Line, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StrokeAlignment,
+};
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::barcode::Symbology;
+use crate::barcode_label::draw_barcode;
+use crate::files;
+use crate::fonts;
+use crate::framebuffer::Framebuffer;
+use crate::image::load_bitmap;
+use crate::ini::{self, fill, Section};
+use crate::label::{builtin_face, builtin_faces, draw_text, Align, Area, Canvas, Face};
+use crate::qr::{Ecc, QrCode};
+use crate::qr_label::draw_qr;
+use crate::settings::Settings;
+use crate::tag::TagSize;
+
+/// App data subfolder the templates are read from.
+pub const FOLDER: &str = "templates";
+
+/// Room for a typed placeholder value, including the nul.
+pub const INPUT_SIZE: usize = 64;
+
+/// Something drawn on the label, strings may hold `{placeholders}`.
+enum Element {
+    Text {
+        area: Area,
+        /// A built-in font such as `6x10` or a BDF file, all built-in fonts when `None`.
+        font: Option<String>,
+        align: Align,
+        text: String,
+    },
+    Qr {
+        area: Area,
+        ecc: Ecc,
+        data: String,
+    },
+    Barcode {
+        area: Area,
+        symbology: Symbology,
+        data: String,
+        caption: bool,
+    },
+    Image {
+        left: usize,
+        top: usize,
+        file: String,
+    },
+    Line {
+        from: Point,
+        to: Point,
+        thickness: u32,
+    },
+    Rect {
+        area: Area,
+        fill: bool,
+        thickness: u32,
+    },
+}
+
+impl Section<'_> {
+    fn area(&self) -> Option<Area> {
+        Some(Area {
+            left: self.number("x")?,
+            top: self.number("y")?,
+            width: self.number("width")?,
+            height: self.number("height")?,
+        })
+    }
+
+    fn point(&self, x: &str, y: &str) -> Option<Point> {
+        Some(Point::new(self.number(x)? as i32, self.number(y)? as i32))
+    }
+
+    fn element(&self) -> Option<Element> {
+        let element = match self.kind {
+            "text" => Element::Text {
+                area: self.area()?,
+                font: self.get("font").map(String::from),
+                align: self
+                    .get("align")
+                    .map_or(Some(Align::Center), Align::from_name)?,
+                text: self.get("text")?.into(),
+            },
+            "qr" => {
+                let size = self.number("size")?;
+                Element::Qr {
+                    area: Area {
+                        left: self.number("x")?,
+                        top: self.number("y")?,
+                        width: size,
+                        height: size,
+                    },
+                    ecc: self
+                        .get("level")
+                        .map_or(Some(Ecc::Medium), Ecc::from_name)?,
+                    data: self.get("data")?.into(),
+                }
+            }
+            "barcode" => Element::Barcode {
+                area: self.area()?,
+                symbology: Symbology::from_name(self.get("type")?)?,
+                data: self.get("data")?.into(),
+                caption: self.flag_or("caption", true)?,
+            },
+            "image" => Element::Image {
+                left: self.number("x")?,
+                top: self.number("y")?,
+                file: self.get("file")?.into(),
+            },
+            "line" => Element::Line {
+                from: self.point("x1", "y1")?,
+                to: self.point("x2", "y2")?,
+                thickness: self.number_or("thickness", 1)? as u32,
+            },
+            "rect" => Element::Rect {
+                area: self.area()?,
+                fill: self.flag_or("fill", false)?,
+                thickness: self.number_or("thickness", 1)? as u32,
+            },
+            _ => return None,
+        };
+        Some(element)
+    }
+}
+
+impl Element {
+    /// Strings of the element that placeholders are filled into.
+    fn strings(&self) -> &[String] {
+        match self {
+            Self::Text { text, .. } => core::slice::from_ref(text),
+            Self::Qr { data, .. } | Self::Barcode { data, .. } => core::slice::from_ref(data),
+            Self::Image { file, .. } => core::slice::from_ref(file),
+            Self::Line { .. } | Self::Rect { .. } => &[],
+        }
+    }
+
+    fn draw<'v>(
+        &self,
+        canvas: &mut Canvas,
+        value: &impl Fn(&str) -> &'v str,
+    ) -> Result<(), *const c_char> {
+        match self {
+            Self::Text {
+                area,
+                font,
+                align,
+                text,
+            } => {
+                let text = fill(text, value);
+                let lines = ini::lines(&text);
+                // only the glyphs of the text are kept, a whole font may not fit in memory
+                let bdf;
+                let faces = match font.as_deref() {
+                    None => builtin_faces(),
+                    Some(name) => match builtin_face(name) {
+                        Some(face) => vec![face],
+                        None => {
+                            bdf = fonts::load(name, |c| text.contains(c))
+                                .ok_or(c_string!("Can't load font"))?;
+                            vec![Face::Bdf(&bdf)]
+                        }
+                    },
+                };
+                draw_text(canvas, &faces, &lines, *align, area);
+            }
+            Self::Qr { area, ecc, data } => {
+                let code = QrCode::encode(fill(data, value).as_bytes(), *ecc, None)
+                    .ok_or(c_string!("Text too long for QR code"))?;
+                draw_qr(canvas, &code, area)?;
+            }
+            Self::Barcode {
+                area,
+                symbology,
+                data,
+                caption,
+            } => draw_barcode(canvas, *symbology, &fill(data, value), area, *caption)?,
+            Self::Image { left, top, file } => {
+                let file = fill(file, value);
+                let mut path = FuriString::new();
+                if file.starts_with('/') {
+                    let _ = uwrite!(path, "{}", file.as_str());
+                } else {
+                    path = files::app_data_path(&file);
+                }
+                let bitmap = load_bitmap(path.as_c_str()).map_err(|error| error.message())?;
+                let pixels = (0..bitmap.height)
+                    .flat_map(|y| (0..bitmap.width).map(move |x| (x, y)))
+                    .filter(|&(x, y)| bitmap.is_black(x, y))
+                    .map(|(x, y)| {
+                        let point = Point::new((left + x) as i32, (top + y) as i32);
+                        Pixel(point, BinaryColor::On)
+                    });
+                let _ = canvas.draw_iter(pixels);
+            }
+            Self::Line {
+                from,
+                to,
+                thickness,
+            } => {
+                let style = PrimitiveStyle::with_stroke(BinaryColor::On, *thickness);
+                let _ = Line::new(*from, *to).into_styled(style).draw(canvas);
+            }
+            Self::Rect {
+                area,
+                fill,
+                thickness,
+            } => {
+                let mut style = PrimitiveStyleBuilder::new();
+                if *fill {
+                    style = style.fill_color(BinaryColor::On);
+                } else {
+                    style = style
+                        .stroke_color(BinaryColor::On)
+                        .stroke_width(*thickness)
+                        .stroke_alignment(StrokeAlignment::Inside);
+                }
+                let corner = Point::new(area.left as i32, area.top as i32);
+                let size = Size::new(area.width as u32, area.height as u32);
+                let _ = Rectangle::new(corner, size)
+                    .into_styled(style.build())
+                    .draw(canvas);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A label layout read from an INI file in the templates folder.
+pub struct Template {
+    sizes: Vec<TagSize>,
+    elements: Vec<Element>,
+    /// Placeholder names in the order they first appear.
+    placeholders: Vec<String>,
+}
+
+impl Template {
+    /// Template files in the templates folder.
+    pub fn list() -> Vec<FuriString> {
+        files::names_in(FOLDER, ".ini")
+    }
+
+    /// Reads and checks `name` from the templates folder, bad sections are logged.
+    pub fn load(name: &str) -> Result<Self, *const c_char> {
+        let mut path = files::app_data_path(FOLDER);
+        let _ = uwrite!(path, "/{}", name);
+        let data = files::read_to_end(path.as_c_str()).ok_or(c_string!("Can't read template"))?;
+
+        let sections = ini::parse(&data).map_err(|line| {
+            println!("bad template line {}", line);
+            c_string!("Bad template")
+        })?;
+
+        let mut template = Template {
+            sizes: Vec::new(),
+            elements: Vec::new(),
+            placeholders: Vec::new(),
+        };
+        for section in &sections {
+            let parsed = if section.kind == "template" {
+                section.get("sizes").and_then(|sizes| {
+                    sizes
+                        .split(',')
+                        .map(|size| TagSize::from_name(size.trim()))
+                        .collect::<Option<Vec<_>>>()
+                        .map(|sizes| template.sizes.extend(sizes))
+                })
+            } else {
+                section
+                    .element()
+                    .map(|element| template.elements.push(element))
+            };
+            if parsed.is_none() {
+                println!(
+                    "bad template section {} at line {}",
+                    section.kind, section.line
+                );
+                return Err(c_string!("Bad template"));
+            }
+        }
+        if template.sizes.is_empty() {
+            return Err(c_string!("Template has no sizes"));
+        }
+
+        template.placeholders = ini::placeholders(
+            template
+                .elements
+                .iter()
+                .flat_map(|element| element.strings().iter().map(String::as_str)),
+        );
+        Ok(template)
+    }
+
+    pub fn placeholders(&self) -> &[String] {
+        &self.placeholders
+    }
+
+    /// True if the template is laid out for the panel.
+    pub fn fits(&self, tag_size: TagSize) -> bool {
+        self.sizes.contains(&tag_size)
+    }
+
+    /// Draws the elements in file order, with `value` giving the text for each placeholder.
+    pub fn render<'v>(
+        &self,
+        value: impl Fn(&str) -> &'v str,
+        settings: &Settings,
+    ) -> Result<Framebuffer, *const c_char> {
+        if !self.fits(settings.tag_size) {
+            return Err(c_string!("Template not for this panel"));
+        }
+        let mut framebuffer = Framebuffer::new(settings.tag_size);
+        let mut canvas = Canvas::new(&mut framebuffer, settings.rotation);
+        for element in &self.elements {
+            element.draw(&mut canvas, &value)?;
+        }
+
+        if settings.invert {
+            framebuffer.invert();
+        }
+        Ok(framebuffer)
+    }
+}
+
+/// A template picked on the Flipper, with the values typed for its placeholders.
+pub struct Form {
+    pub name: FuriString,
+    pub template: Template,
+    /// One per placeholder, kept when the same template is picked again.
+    values: Vec<String>,
+    /// Placeholder being typed.
+    pub field: usize,
+    pub input: [c_char; INPUT_SIZE],
+}
+
+impl Form {
+    /// Loads the template, reusing the values typed last time it was picked.
+    pub fn open(name: &CStr, previous: Option<Form>) -> Result<Self, *const c_char> {
+        let template = Template::load(name.to_str().unwrap_or(""))?;
+        let previous = previous.filter(|form| form.name.as_c_str() == name);
+        let values = template
+            .placeholders
+            .iter()
+            .map(|placeholder| {
+                previous
+                    .as_ref()
+                    .and_then(|form| form.value(placeholder))
+                    .unwrap_or("")
+                    .into()
+            })
+            .collect();
+        let mut owned = FuriString::new();
+        let _ = uwrite!(owned, "{}", name.to_str().unwrap_or(""));
+        Ok(Self {
+            name: owned,
+            template,
+            values,
+            field: 0,
+            input: [0; INPUT_SIZE],
+        })
+    }
+
+    fn value(&self, placeholder: &str) -> Option<&str> {
+        let index = self
+            .template
+            .placeholders
+            .iter()
+            .position(|name| name == placeholder)?;
+        Some(&self.values[index])
+    }
+
+    /// Name of the placeholder being typed, `None` once all are done.
+    pub fn placeholder(&self) -> Option<&str> {
+        self.template
+            .placeholders
+            .get(self.field)
+            .map(String::as_str)
+    }
+
+    /// Puts the value of the current placeholder in the input to be edited.
+    pub fn edit(&mut self) {
+        self.input = [0; INPUT_SIZE];
+        if let Some(value) = self.values.get(self.field) {
+            for (dst, src) in self.input.iter_mut().zip(value.as_bytes()) {
+                *dst = *src as c_char;
+            }
+        }
+    }
+
+    /// Stores the input for the current placeholder and moves to the next one.
+    pub fn save(&mut self) {
+        let input = unsafe { CStr::from_ptr(self.input.as_ptr()) };
+        if let Some(value) = self.values.get_mut(self.field) {
+            *value = input.to_str().unwrap_or("").into();
+        }
+        self.field += 1;
+    }
+
+    pub fn render(&self, settings: &Settings) -> Result<Framebuffer, *const c_char> {
+        self.template
+            .render(|name| self.value(name).unwrap_or(""), settings)
+    }
+}
@@ -0,0 +1,38 @@
+//! Frames exchanged with a selected tag. Has no Flipper dependencies so the host tools can build
+//! the drivers too.
+
+/// What a tag told us about itself during anticollision.
+#[derive(Clone, Copy)]
+pub struct TagInfo {
+    uid: [u8; 10],
+    uid_len: u8,
+    pub atqa: [u8; 2],
+    pub sak: u8,
+}
+
+impl TagInfo {
+    /// Identity of a tag, `uid` is 4, 7 or 10 bytes long.
+    pub fn new(uid: &[u8], atqa: [u8; 2], sak: u8) -> Self {
+        let mut tag = Self {
+            uid: [0; 10],
+            uid_len: uid.len() as u8,
+            atqa,
+            sak,
+        };
+        tag.uid[..uid.len()].copy_from_slice(uid);
+        tag
+    }
+
+    pub fn uid(&self) -> &[u8] {
+        &self.uid[..self.uid_len as usize]
+    }
+}
+
+/// Exchanges raw frames with a selected tag, so drivers don't depend on the NFC stack.
+pub trait Transport {
+    /// Sends a frame and copies the response into `rx`. Returns the number of response bits,
+    /// or `None` if the tag didn't answer.
+    fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], timeout: u32) -> Option<usize>;
+
+    fn delay_ms(&mut self, ms: u32);
+}
@@ -0,0 +1,136 @@
+use core::ffi::c_char;
+use flipperzero::furi::string::FuriString;
+use flipperzero::println;
+use flipperzero_sys as sys;
+use sys::c_string;
+use ufmt::uwrite;
+
+use crate::cache::Cache;
+use crate::capture::Capture;
+use crate::driver::{self, EpaperTag, Link, Progress, Status, WriteError, WriteStats};
+use crate::history;
+use crate::image::Image;
+use crate::poller;
+use crate::settings::Settings;
+use crate::transport::{TagInfo, Transport};
+use crate::update_widget;
+
+pub struct WidgetProgress(pub *mut sys::Widget);
+
+impl Progress for WidgetProgress {
+    fn update(&mut self, status: Status) {
+        match status {
+            Status::SettingUp => update_widget(self.0, c_string!("setting up")),
+            Status::Chunk(i, loops) => {
+                let mut progress = FuriString::new();
+                let _ = uwrite!(progress, "chunk {}/{}", i, loops);
+                update_widget(self.0, progress.as_c_str().as_ptr());
+            }
+            Status::Finishing => update_widget(self.0, c_string!("finishing")),
+            Status::Done => update_widget(self.0, c_string!("done!")),
+        }
+    }
+}
+
+pub struct ConsoleProgress;
+
+impl Progress for ConsoleProgress {
+    fn update(&mut self, status: Status) {
+        match status {
+            Status::SettingUp => println!("setting up"),
+            // keep the console readable on the 400 chunk 7.5" panel
+            Status::Chunk(i, loops) if i % 10 == 0 || i == loops => {
+                println!("chunk {}/{}", i, loops)
+            }
+            Status::Chunk(..) => {}
+            Status::Finishing => println!("finishing"),
+            Status::Done => println!("done!"),
+        }
+    }
+}
+
+impl WriteError {
+    pub fn message(&self) -> *const c_char {
+        match self {
+            Self::Command => c_string!("Tag didn't respond"),
+            Self::Data => c_string!("Image upload failed"),
+            Self::Refresh => c_string!("Refresh timed out"),
+            Self::Unsupported => c_string!("Tag can't show image"),
+            Self::NoTag => c_string!("Tag lost"),
+        }
+    }
+}
+
+/// True if the tag was last written with this image.
+pub fn is_up_to_date(image: &Image, tag: &TagInfo, cache: &Cache) -> bool {
+    cache.get(tag.uid()) == Some(image.hash())
+}
+
+/// Waits up to the detect timeout for a tag one of the drivers can write.
+pub fn detect(settings: &Settings) -> Option<TagInfo> {
+    poller::detect_any(settings).filter(|tag| driver::find(tag).is_some())
+}
+
+/// Uploads an image to a detected tag and waits for the panel to refresh.
+pub fn write_tag(
+    driver: &dyn EpaperTag,
+    transport: &mut dyn Transport,
+    image: &Image,
+    settings: &Settings,
+    progress: &mut dyn Progress,
+    stats: &mut WriteStats,
+) -> Result<(), WriteError> {
+    let mut link = Link::new(transport, settings.timing());
+    let result = driver::write(driver, &mut link, image.tag_size, &[&image.data], progress);
+    *stats = link.stats;
+    result
+}
+
+/// Writes a detected tag once it's selected again, appends the attempt to the history log and
+/// remembers what the tag shows.
+pub fn write_and_log(
+    image: &Image,
+    settings: &Settings,
+    tag: &TagInfo,
+    cache: &mut Cache,
+    progress: &mut dyn Progress,
+) -> Result<(), WriteError> {
+    let start = unsafe { sys::furi_get_tick() };
+    let mut stats = WriteStats::default();
+    let mut result = Err(WriteError::NoTag);
+    let found = poller::with_tag(settings, &mut |selected, transport| {
+        // a different tag was swapped in since it was detected
+        if selected.uid() != tag.uid() {
+            return;
+        }
+        let mut transport = Capture::new(transport, selected, settings.capture);
+        result = match driver::find(selected) {
+            Some(driver) => write_tag(
+                driver,
+                &mut transport,
+                image,
+                settings,
+                progress,
+                &mut stats,
+            ),
+            None => Err(WriteError::Unsupported),
+        };
+    });
+    if !found {
+        return result;
+    }
+
+    history::Entry {
+        tag,
+        tag_size: image.tag_size,
+        image,
+        stats: &stats,
+        elapsed_ms: history::elapsed_ms(start),
+        result: &result,
+    }
+    .append();
+    if result.is_ok() {
+        cache.set(tag.uid(), image.hash());
+    }
+    result
+}
@@ -0,0 +1,351 @@
+//! Encodes data the way the app's code sources do and saves the symbol as a PBM image, so it can
+//! be checked with a reference decoder such as ZXing or `dmtxread`.
+//!
+//! ```
+//! cargo run -- qr "https://example.com" qr.pbm
+//! cargo run -- datamatrix "A1-0042" datamatrix.pbm
+//! cargo run -- pdf417 "A1-0042" pdf417.pbm
+//! cargo run -- ean13 590123412345 ean.pbm
+//! ```
+//!
+//! `cargo test` here runs the tests of the shared encoders, font parser and template reader,
+//! compares the QR codes with the qrcodegen crate and the PDF417 symbols with the pdf417 crate,
+//! and reads the symbols back with rxing, a port of ZXing.
+
+extern crate alloc;
+
+// shared with the app
+#[allow(dead_code)]
+#[path = "../../../src/barcode.rs"]
+mod barcode;
+#[allow(dead_code)]
+#[path = "../../../src/bdf.rs"]
+mod bdf;
+#[allow(dead_code)]
+#[path = "../../../src/datamatrix.rs"]
+mod datamatrix;
+#[allow(dead_code)]
+#[path = "../../../src/ini.rs"]
+mod ini;
+#[allow(dead_code)]
+#[path = "../../../src/pdf417.rs"]
+mod pdf417;
+#[allow(dead_code)]
+#[path = "../../../src/qr.rs"]
+mod qr;
+
+use std::process::ExitCode;
+use std::{env, fs};
+
+use barcode::{Barcode, Symbology};
+use datamatrix::DataMatrix;
+use pdf417::Pdf417;
+use qr::{Ecc, QrCode};
+
+const USAGE: &str =
+    "usage: waveshare-codes qr|datamatrix|pdf417|code128|ean13|upca|code39 <data> <out.pbm> [pixels per module]";
+
+/// Height of linear barcodes in modules.
+const BAR_HEIGHT: usize = 50;
+
+/// Modules from the top left, quiet zone included.
+struct Symbol {
+    width: usize,
+    height: usize,
+    dark: Vec<bool>,
+}
+
+impl Symbol {
+    fn new(
+        width: usize,
+        height: usize,
+        quiet_zone: usize,
+        module: impl Fn(usize, usize) -> bool,
+    ) -> Self {
+        let (full_width, full_height) = (width + 2 * quiet_zone, height + 2 * quiet_zone);
+        let mut dark = vec![false; full_width * full_height];
+        for y in 0..height {
+            for x in 0..width {
+                dark[(y + quiet_zone) * full_width + x + quiet_zone] = module(x, y);
+            }
+        }
+        Self {
+            width: full_width,
+            height: full_height,
+            dark,
+        }
+    }
+
+    /// A binary PBM with each module `scale` pixels square.
+    fn pbm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut data = format!("P4\n{width} {height}\n").into_bytes();
+        let stride = width.div_ceil(8);
+        for y in 0..height {
+            let mut row = vec![0u8; stride];
+            for x in 0..width {
+                if self.dark[y / scale * self.width + x / scale] {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            data.extend(row);
+        }
+        data
+    }
+}
+
+fn encode(kind: &str, data: &str) -> Result<Symbol, String> {
+    let symbology = match kind {
+        "qr" => {
+            let code = QrCode::encode(data.as_bytes(), Ecc::Medium, None)
+                .ok_or("too long for a QR code")?;
+            let size = code.size();
+            return Ok(Symbol::new(size, size, qr::QUIET_ZONE, |x, y| {
+                code.module(x, y)
+            }));
+        }
+        "datamatrix" => {
+            // as large an area as a 7.5" panel, so the smallest fitting size wins ties
+            let matrix =
+                DataMatrix::encode(data.as_bytes(), 800, 480).ok_or("too long for a DataMatrix")?;
+            return Ok(Symbol::new(
+                matrix.cols(),
+                matrix.rows(),
+                datamatrix::QUIET_ZONE,
+                |x, y| matrix.module(x, y),
+            ));
+        }
+        "pdf417" => {
+            let symbol =
+                Pdf417::encode(data.as_bytes(), 800, 480).ok_or("too long for a PDF417")?;
+            return Ok(Symbol::new(
+                symbol.cols(),
+                symbol.rows(),
+                pdf417::QUIET_ZONE,
+                |x, y| symbol.module(x, y),
+            ));
+        }
+        "code128" => Symbology::Code128,
+        "ean13" => Symbology::Ean13,
+        "upca" => Symbology::UpcA,
+        "code39" => Symbology::Code39,
+        _ => return Err(USAGE.into()),
+    };
+    let barcode =
+        Barcode::encode(symbology, data).map_err(|error| error.to_string_lossy().into_owned())?;
+    println!("text: {}", barcode.text());
+    let modules = barcode.modules();
+    Ok(Symbol::new(
+        modules.len(),
+        BAR_HEIGHT,
+        barcode::QUIET_ZONE,
+        |x, _| modules[x],
+    ))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (kind, data, path) = match &args[..] {
+        [kind, data, path] | [kind, data, path, _] => (kind, data, path),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let scale = match args.get(3).map(|scale| scale.parse()) {
+        None => 4,
+        Some(Ok(scale)) if scale > 0 => scale,
+        Some(_) => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let symbol = match encode(kind, data) {
+        Ok(symbol) => symbol,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = fs::write(path, symbol.pbm(scale)) {
+        eprintln!("can't write {path}: {error}");
+        return ExitCode::FAILURE;
+    }
+    println!("{} by {} modules", symbol.width, symbol.height);
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qrcodegen::{QrCodeEcc, QrSegment, Version};
+    use rxing::BarcodeFormat;
+
+    /// Compares QR codes of random bytes with the qrcodegen crate, across lengths, error
+    /// correction levels and fixed versions.
+    #[test]
+    fn qr_matches_qrcodegen() {
+        let eccs = [
+            (Ecc::Low, QrCodeEcc::Low),
+            (Ecc::Medium, QrCodeEcc::Medium),
+            (Ecc::Quartile, QrCodeEcc::Quartile),
+            (Ecc::High, QrCodeEcc::High),
+        ];
+        let mut seed: u32 = 12345;
+        let mut checked = 0;
+        for len in [
+            0, 1, 5, 17, 30, 60, 100, 200, 300, 500, 800, 1200, 1800, 2500,
+        ] {
+            let data: Vec<u8> = (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    (seed >> 16) as u8
+                })
+                .collect();
+            for (ecc, reference_ecc) in eccs {
+                for version in [None, Some(10), Some(25), Some(40)] {
+                    let (min, max) = version.map_or((1, 40), |version| (version, version));
+                    let reference = qrcodegen::QrCode::encode_segments_advanced(
+                        &[QrSegment::make_bytes(&data)],
+                        reference_ecc,
+                        Version::new(min),
+                        Version::new(max),
+                        None,
+                        false,
+                    );
+                    let case = format!("{len} bytes, ecc {}, version {version:?}", ecc as u8);
+                    match (QrCode::encode(&data, ecc, version), reference) {
+                        (Some(code), Ok(reference)) => {
+                            assert_eq!(code.size(), reference.size() as usize, "{case}");
+                            for y in 0..code.size() {
+                                for x in 0..code.size() {
+                                    assert_eq!(
+                                        code.module(x, y),
+                                        reference.get_module(x as i32, y as i32),
+                                        "{case}: module {x},{y}"
+                                    );
+                                }
+                            }
+                            checked += 1;
+                        }
+                        (None, Err(_)) => {}
+                        (code, reference) => panic!(
+                            "{case}: encoded {}, qrcodegen encoded {}",
+                            code.is_some(),
+                            reference.is_ok()
+                        ),
+                    }
+                }
+            }
+        }
+        assert_eq!(checked, 175);
+    }
+
+    /// Bytes from a fixed seed, the same on every run.
+    fn random_bytes(len: usize, seed: &mut u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (*seed >> 16) as u8
+            })
+            .collect()
+    }
+
+    /// Compares PDF417 symbols of random bytes with the pdf417 crate, given the same rows,
+    /// columns and error correction level.
+    #[test]
+    fn pdf417_matches_pdf417_crate() {
+        let mut seed: u32 = 54321;
+        for len in [1, 2, 5, 6, 7, 12, 30, 100, 190, 300, 600, 1000] {
+            let data = random_bytes(len, &mut seed);
+            let symbol = Pdf417::encode(&data, 800, 480).unwrap();
+            let rows = symbol.rows() / pdf417::ROW_HEIGHT;
+            let cols = (symbol.cols() - 69) / 17;
+            // the length, the latch or shift to bytes, and the bytes five codewords per six
+            let count = 2 + len / 6 * 5 + len % 6;
+            let level = match count {
+                0..=40 => 2,
+                41..=160 => 3,
+                161..=320 => 4,
+                _ => 5,
+            };
+            let mut codewords = vec![0u16; rows * cols];
+            pdf417_reference::PDF417Encoder::new(&mut codewords, false)
+                .append_bytes(&data)
+                .seal(level);
+            let mut modules = vec![false; symbol.cols() * rows];
+            pdf417_reference::PDF417::new(&codewords, rows as u8, cols as u8, level)
+                .render(&mut modules[..]);
+            for row in 0..rows {
+                for x in 0..symbol.cols() {
+                    assert_eq!(
+                        symbol.module(x, row * pdf417::ROW_HEIGHT),
+                        modules[row * symbol.cols() + x],
+                        "{len} bytes: row {row}, module {x}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reads a symbol back with rxing, three pixels a module.
+    fn decode(symbol: &Symbol, format: BarcodeFormat) -> String {
+        let scale = 3;
+        let (width, height) = (symbol.width * scale, symbol.height * scale);
+        let luma = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width / scale, i / width / scale);
+                if symbol.dark[y * symbol.width + x] {
+                    0
+                } else {
+                    255
+                }
+            })
+            .collect();
+        match rxing::helpers::detect_in_luma(luma, width as u32, height as u32, Some(format)) {
+            Ok(result) => result.getText().to_string(),
+            Err(error) => panic!("no {format:?} found: {error}"),
+        }
+    }
+
+    const TEXTS: [&str; 5] = [
+        "A",
+        "123456",
+        "A1-0042",
+        "https://example.com/badge?id=A1042",
+        "The quick brown fox jumps over the lazy dog, 0123456789 times.",
+    ];
+
+    #[test]
+    fn pdf417_reads_back_with_rxing() {
+        for text in TEXTS {
+            let symbol = encode("pdf417", text).unwrap();
+            assert_eq!(decode(&symbol, BarcodeFormat::PDF_417), text);
+        }
+    }
+
+    #[test]
+    fn datamatrix_reads_back_with_rxing() {
+        for text in TEXTS {
+            let symbol = encode("datamatrix", text).unwrap();
+            assert_eq!(decode(&symbol, BarcodeFormat::DATA_MATRIX), text);
+        }
+    }
+
+    #[test]
+    fn code128_reads_back_with_rxing() {
+        // the last ones switch between code sets B and C
+        for text in TEXTS.iter().chain(&["1234A", "A12345", "AB12345678"]) {
+            let symbol = encode("code128", text).unwrap();
+            assert_eq!(decode(&symbol, BarcodeFormat::CODE_128), *text);
+        }
+    }
+
+    #[test]
+    fn code39_reads_back_with_rxing() {
+        for text in ["A", "123456", "A1-0042", "CODE 39 $/+%."] {
+            let symbol = encode("code39", text).unwrap();
+            assert_eq!(decode(&symbol, BarcodeFormat::CODE_39), text);
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Replays a write captured with the app's "Capture" setting against the simulated tag, reports
+//! where the real tag answered differently and saves what the simulated panel ends up showing.
+//! With `--reader`, writes an image to the simulated tag the way the app does instead, checking
+//! the model the emulation mode answers with.
+//!
+//! ```
+//! cargo run -- capture-5753445A31306D010203-1700000000.pcap sent.pbm
+//! cargo run -- --reader label.pbm
+//! ```
+//!
+//! `cargo test` here runs the tests of the app modules it shares, which need no Flipper, and
+//! replays captures of writes to the simulated tag.
+
+extern crate alloc;
+
+// shared with the app, which uses the parts this tool doesn't
+#[allow(dead_code)]
+#[path = "../../../src/driver/mod.rs"]
+mod driver;
+#[allow(dead_code)]
+#[path = "../../../src/framebuffer.rs"]
+mod framebuffer;
+#[allow(dead_code)]
+#[path = "../../../src/graphics.rs"]
+mod graphics;
+#[allow(dead_code)]
+#[path = "../../../src/sim.rs"]
+mod sim;
+#[allow(dead_code)]
+#[path = "../../../src/tag.rs"]
+mod tag;
+#[allow(dead_code)]
+#[path = "../../../src/transport.rs"]
+mod transport;
+
+use std::process::ExitCode;
+use std::{env, fs};
+
+use driver::{Link, Progress, Status, Timing};
+use framebuffer::Framebuffer;
+use sim::SimTag;
+use tag::TagSize;
+use transport::{TagInfo, Transport};
+
+const LINKTYPE_ISO_14443: u32 = 264;
+
+const EVENT_PICC_TO_PCD: u8 = 0xFF;
+const EVENT_PCD_TO_PICC: u8 = 0xFE;
+const EVENT_PICC_TO_PCD_CRC_DROPPED: u8 = 0xFB;
+const EVENT_PCD_TO_PICC_CRC_DROPPED: u8 = 0xFA;
+/// Written by the app after a response ending in a partial byte, with the bits received.
+const EVENT_RX_BITS: u8 = 0x80;
+
+enum Direction {
+    Sent,
+    Received,
+}
+
+struct Frame {
+    direction: Direction,
+    data: Vec<u8>,
+    /// Bits received or sent, less than whole bytes if the last byte is partial.
+    bits: usize,
+}
+
+fn u32_at(data: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    })
+}
+
+/// Reads the frames of a pcap file with ISO 14443 link type, dropping CRCs if present.
+fn parse_pcap(data: &[u8]) -> Result<Vec<Frame>, String> {
+    let big_endian = match data.get(..4) {
+        Some([0xD4, 0xC3, 0xB2, 0xA1]) => false,
+        Some([0xA1, 0xB2, 0xC3, 0xD4]) => true,
+        _ => return Err("not a pcap file".into()),
+    };
+    let link_type = u32_at(data, 20, big_endian).ok_or("truncated header")?;
+    if link_type != LINKTYPE_ISO_14443 {
+        return Err(format!("link type {link_type} isn't ISO 14443"));
+    }
+
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut offset = 24;
+    while offset < data.len() {
+        let len = u32_at(data, offset + 8, big_endian).ok_or("truncated packet header")? as usize;
+        let packet = data
+            .get(offset + 16..offset + 16 + len)
+            .ok_or("truncated packet")?;
+        offset += 16 + len;
+
+        // pseudo header: version, event, big endian length
+        let [0, event, _, _, payload @ ..] = packet else {
+            return Err("bad ISO 14443 pseudo header".into());
+        };
+        let (direction, crc) = match *event {
+            EVENT_PCD_TO_PICC => (Direction::Sent, 2),
+            EVENT_PICC_TO_PCD => (Direction::Received, 2),
+            EVENT_PCD_TO_PICC_CRC_DROPPED => (Direction::Sent, 0),
+            EVENT_PICC_TO_PCD_CRC_DROPPED => (Direction::Received, 0),
+            EVENT_RX_BITS => {
+                let (Some(frame), &[high, low]) = (frames.last_mut(), payload) else {
+                    return Err("bad received bits event".into());
+                };
+                frame.bits = u16::from_be_bytes([high, low]) as usize;
+                continue;
+            }
+            _ => continue, // field on and off
+        };
+        let data = payload[..payload.len().saturating_sub(crc)].to_vec();
+        let bits = data.len() * 8;
+        frames.push(Frame {
+            direction,
+            data,
+            bits,
+        });
+    }
+    Ok(frames)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Hands frames straight to the simulated tag.
+struct SimTransport<'a>(&'a mut SimTag);
+
+impl Transport for SimTransport<'_> {
+    fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], _timeout: u32) -> Option<usize> {
+        let response = self.0.exchange(tx)?;
+        rx[..response.len()].copy_from_slice(response);
+        Some(response.len() * 8)
+    }
+
+    fn delay_ms(&mut self, _ms: u32) {}
+}
+
+struct NoProgress;
+
+impl Progress for NoProgress {
+    fn update(&mut self, _status: Status) {}
+}
+
+/// Writes the framebuffer with the app's driver for the tag, without resending frames so any
+/// frame the simulator refuses fails the write.
+fn write(tag: &mut SimTag, framebuffer: &Framebuffer) -> Result<(), String> {
+    let info = TagInfo::new(&sim::UID, sim::ATQA, sim::SAK);
+    let driver = driver::find(&info).ok_or("no driver for the simulated tag")?;
+    let timing = Timing {
+        tx_timeout: 300,
+        retries: 0,
+        refresh_timeout: 5000,
+    };
+    let mut transport = SimTransport(tag);
+    let mut link = Link::new(&mut transport, timing);
+    let planes = [framebuffer.data()];
+    driver::write(
+        driver,
+        &mut link,
+        framebuffer.tag_size(),
+        &planes,
+        &mut NoProgress,
+    )
+    .map_err(|error| format!("{} error after {} chunks", error.name(), link.stats.chunks))
+}
+
+fn reader(image_path: &str) -> ExitCode {
+    let pbm = match fs::read(image_path) {
+        Ok(pbm) => pbm,
+        Err(error) => {
+            eprintln!("can't read {image_path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(tag_size) = TagSize::ALL
+        .into_iter()
+        .find(|tag_size| pbm.starts_with(tag_size.header()))
+    else {
+        eprintln!("{image_path} isn't a P4 image the size of a panel");
+        return ExitCode::FAILURE;
+    };
+
+    let mut framebuffer = Framebuffer::new(tag_size);
+    framebuffer.blit(
+        0,
+        0,
+        tag_size.width(),
+        tag_size.height(),
+        &pbm[tag_size.header().len()..],
+    );
+
+    let mut tag = SimTag::new();
+    if let Err(error) = write(&mut tag, &framebuffer) {
+        eprintln!("write failed, {error}");
+        return ExitCode::FAILURE;
+    }
+    if tag.pbm().as_ref() != Some(&pbm) {
+        eprintln!("the simulated panel shows a different image");
+        return ExitCode::FAILURE;
+    }
+    println!("{}\" panel shows the image", tag_size.name());
+    ExitCode::SUCCESS
+}
+
+/// Sends the captured frames to the simulated tag, printing every one answered differently.
+/// Returns the number of frames sent and of mismatches.
+fn replay_frames(frames: &[Frame], tag: &mut SimTag) -> (usize, usize) {
+    let mut sent = 0;
+    let mut mismatches = 0;
+    let mut frames = frames.iter().peekable();
+    while let Some(frame) = frames.next() {
+        let Direction::Sent = frame.direction else {
+            continue; // a response without a frame before it
+        };
+        sent += 1;
+        let response = frames.next_if(|next| matches!(next.direction, Direction::Received));
+        // the simulator only answers whole bytes
+        let partial = response.filter(|response| response.bits != response.data.len() * 8);
+        let captured = response.map(|response| &response.data[..]);
+        let mut simulated = tag.exchange(&frame.data);
+        // real panels take varying time to refresh, keep the simulator in step with the capture
+        let is_wait_poll = frame.data == [0xCD, 0x0A];
+        if is_wait_poll && captured == Some(sim::READY) {
+            while simulated == Some(sim::BUSY) {
+                simulated = tag.exchange(&frame.data);
+            }
+        }
+        if partial.is_some()
+            || simulated != captured && !(is_wait_poll && captured == Some(sim::BUSY))
+        {
+            mismatches += 1;
+            let mut answer = captured.map_or("nothing".into(), hex);
+            if let Some(response) = partial {
+                answer += &format!(" ({} bits)", response.bits);
+            }
+            println!(
+                "frame {sent} {}: tag answered {answer}, simulator {}",
+                hex(&frame.data),
+                simulated.map_or("nothing".into(), hex),
+            );
+        }
+    }
+    (sent, mismatches)
+}
+
+fn replay(capture_path: &str, pbm_path: &str) -> ExitCode {
+    let frames = match fs::read(capture_path)
+        .map_err(|error| error.to_string())
+        .and_then(|data| parse_pcap(&data))
+    {
+        Ok(frames) => frames,
+        Err(error) => {
+            eprintln!("can't read {capture_path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut tag = SimTag::new();
+    let (sent, mismatches) = replay_frames(&frames, &mut tag);
+    println!("{sent} frames sent, {mismatches} answered differently");
+
+    let Some(pbm) = tag.pbm() else {
+        eprintln!("the capture never finished a refresh, no image to save");
+        return ExitCode::FAILURE;
+    };
+    if let Err(error) = fs::write(pbm_path, pbm) {
+        eprintln!("can't write {pbm_path}: {error}");
+        return ExitCode::FAILURE;
+    }
+    println!("saved {pbm_path}");
+    if mismatches > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    match &args[..] {
+        [_, flag, image_path] if flag == "--reader" => reader(image_path),
+        [_, capture_path, pbm_path] => replay(capture_path, pbm_path),
+        _ => {
+            eprintln!("usage: waveshare-replay <capture.pcap> <out.pbm>");
+            eprintln!("       waveshare-replay --reader <image.pbm>");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pcap file of ISO 14443 packets, laid out the way the app's capture writes them.
+    fn pcap(big_endian: bool, packets: &[(u8, &[u8])]) -> Vec<u8> {
+        let u32_bytes = |value: u32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let mut data = u32_bytes(0xA1B2_C3D4).to_vec();
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&u32_bytes(LINKTYPE_ISO_14443));
+        for (event, payload) in packets {
+            let len = 4 + payload.len() as u32;
+            data.extend_from_slice(&[0; 8]);
+            data.extend_from_slice(&u32_bytes(len));
+            data.extend_from_slice(&u32_bytes(len));
+            data.extend_from_slice(&[0, *event]);
+            data.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+            data.extend_from_slice(payload);
+        }
+        data
+    }
+
+    #[test]
+    fn parses_directions_crcs_and_bits() {
+        let packets: [(u8, &[u8]); 6] = [
+            (0xFC, &[]), // field on
+            (EVENT_PCD_TO_PICC, &[0xCD, 0x0D, 0x12, 0x34]),
+            (EVENT_PICC_TO_PCD, &[0x00, 0x00, 0x56, 0x78]),
+            (EVENT_PCD_TO_PICC_CRC_DROPPED, &[0xCD, 0x0A]),
+            (EVENT_PICC_TO_PCD_CRC_DROPPED, &[0x0A]),
+            (EVENT_RX_BITS, &[0x00, 0x04]),
+        ];
+        for big_endian in [false, true] {
+            let frames = parse_pcap(&pcap(big_endian, &packets)).unwrap();
+            let frames: Vec<_> = frames
+                .iter()
+                .map(|frame| {
+                    let sent = matches!(frame.direction, Direction::Sent);
+                    (sent, &frame.data[..], frame.bits)
+                })
+                .collect();
+            assert_eq!(
+                frames,
+                [
+                    (true, &[0xCD, 0x0D][..], 16),
+                    (false, &[0x00, 0x00][..], 16),
+                    (true, &[0xCD, 0x0A][..], 16),
+                    (false, &[0x0A][..], 4),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_foreign_files() {
+        let data = pcap(false, &[(EVENT_PCD_TO_PICC_CRC_DROPPED, &[0xCD, 0x0D])]);
+        let error = |data: &[u8]| parse_pcap(data).err().unwrap_or_default();
+        assert_eq!(error(&data[..data.len() - 1]), "truncated packet");
+        assert_eq!(error(&data[..24 + 10]), "truncated packet header");
+        assert_eq!(error(&data[..22]), "truncated header");
+        assert_eq!(error(&data[4..]), "not a pcap file");
+
+        let mut other = data.clone();
+        other[20..24].copy_from_slice(&1u32.to_le_bytes());
+        assert_eq!(error(&other), "link type 1 isn't ISO 14443");
+
+        let mut bits = pcap(false, &[(EVENT_RX_BITS, &[0x00, 0x04])]);
+        assert_eq!(error(&bits), "bad received bits event");
+        bits = pcap(
+            false,
+            &[
+                (EVENT_PICC_TO_PCD_CRC_DROPPED, &[0x0A]),
+                (EVENT_RX_BITS, &[4]),
+            ],
+        );
+        assert_eq!(error(&bits), "bad received bits event");
+    }
+
+    /// Keeps every frame a write exchanges, as capture events.
+    struct Recorder<'a> {
+        inner: SimTransport<'a>,
+        packets: Vec<(u8, Vec<u8>)>,
+    }
+
+    impl Transport for Recorder<'_> {
+        fn tx_rx(&mut self, tx: &[u8], rx: &mut [u8], timeout: u32) -> Option<usize> {
+            self.packets
+                .push((EVENT_PCD_TO_PICC_CRC_DROPPED, tx.to_vec()));
+            let bits = self.inner.tx_rx(tx, rx, timeout)?;
+            self.packets
+                .push((EVENT_PICC_TO_PCD_CRC_DROPPED, rx[..bits / 8].to_vec()));
+            Some(bits)
+        }
+
+        fn delay_ms(&mut self, _ms: u32) {}
+    }
+
+    /// Writes a framebuffer with a dot in each corner to a simulated tag, returning the capture.
+    fn capture_write(tag_size: TagSize) -> (Framebuffer, Vec<(u8, Vec<u8>)>) {
+        let mut framebuffer = Framebuffer::new(tag_size);
+        let (right, bottom) = (tag_size.width() as i32 - 1, tag_size.height() as i32 - 1);
+        for (x, y) in [(0, 0), (right, 0), (0, bottom), (right, bottom)] {
+            framebuffer.set_pixel(x, y, true);
+        }
+        let mut tag = SimTag::new();
+        let mut recorder = Recorder {
+            inner: SimTransport(&mut tag),
+            packets: Vec::new(),
+        };
+        let info = TagInfo::new(&sim::UID, sim::ATQA, sim::SAK);
+        let timing = Timing {
+            tx_timeout: 300,
+            retries: 0,
+            refresh_timeout: 5000,
+        };
+        let mut link = Link::new(&mut recorder, timing);
+        let planes = [framebuffer.data()];
+        let written = driver::write(
+            driver::find(&info).unwrap(),
+            &mut link,
+            tag_size,
+            &planes,
+            &mut NoProgress,
+        );
+        assert!(written.is_ok());
+        (framebuffer, recorder.packets)
+    }
+
+    fn parse(packets: &[(u8, Vec<u8>)]) -> Vec<Frame> {
+        let packets: Vec<(u8, &[u8])> = packets
+            .iter()
+            .map(|(event, payload)| (*event, &payload[..]))
+            .collect();
+        parse_pcap(&pcap(false, &packets)).unwrap()
+    }
+
+    #[test]
+    fn replays_a_captured_write() {
+        for tag_size in TagSize::ALL {
+            let (framebuffer, packets) = capture_write(tag_size);
+            let frames = parse(&packets);
+            let mut tag = SimTag::new();
+            let (sent, mismatches) = replay_frames(&frames, &mut tag);
+            assert_eq!((sent, mismatches), (packets.len() / 2, 0));
+
+            let pbm = tag.pbm().unwrap();
+            assert_eq!(&pbm[tag_size.header().len()..], framebuffer.data());
+        }
+    }
+
+    #[test]
+    fn reports_responses_that_differ() {
+        let (_, mut packets) = capture_write(TagSize::TwoNine);
+        // a refused chunk, and a response cut short
+        packets[9].1 = vec![0xFF, 0xFF];
+        packets.insert(12, (EVENT_RX_BITS, vec![0x00, 0x0C]));
+        let frames = parse(&packets);
+        assert_eq!(frames[11].bits, 12);
+        let (_, mismatches) = replay_frames(&frames, &mut SimTag::new());
+        assert_eq!(mismatches, 2);
+    }
+}